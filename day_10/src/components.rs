@@ -0,0 +1,438 @@
+use crate::fill::{self, FillRule};
+use crate::interior::enclosed_tiles;
+use crate::{Coord, Direction, Map, Pipe};
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum ComponentKind {
+    /// Every tile connects to two others, so the pipes go round in a circle
+    ClosedLoop,
+    /// A run of pipes with two open ends
+    OpenChain,
+    /// Anything else, such as a lone pipe, tiles branching off an unresolved start, or a
+    /// ring that valves stop from being walked
+    Fragment,
+}
+
+impl Display for ComponentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComponentKind::ClosedLoop => write!(f, "closed loop"),
+            ComponentKind::OpenChain => write!(f, "open chain"),
+            ComponentKind::Fragment => write!(f, "fragment"),
+        }
+    }
+}
+
+/// A set of pipes that are connected to each other, where two neighbouring pipes are
+/// connected if they both point at each other
+#[derive(Debug)]
+pub(crate) struct Component {
+    pub(crate) id: usize,
+    pub(crate) kind: ComponentKind,
+    /// In walk order for loops and chains
+    pub(crate) tiles: Vec<Coord>,
+    /// The number of tiles inside, for closed loops only. Loops that cross themselves use
+    /// the even-odd rule.
+    pub(crate) enclosed: Option<usize>,
+    pub(crate) contains_start: bool,
+}
+
+/// Which components to keep when cleaning a map
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Selection {
+    All,
+    /// The component containing the start
+    Start,
+    Kind(ComponentKind),
+    Ids(Vec<usize>),
+}
+
+impl Selection {
+    /// Parses one of `all`, `start`, `loops`, `chains`, `fragments` or a comma separated
+    /// list of component ids
+    pub(crate) fn parse(input: &str) -> Option<Selection> {
+        match input {
+            "all" => Some(Selection::All),
+            "start" => Some(Selection::Start),
+            "loops" => Some(Selection::Kind(ComponentKind::ClosedLoop)),
+            "chains" => Some(Selection::Kind(ComponentKind::OpenChain)),
+            "fragments" => Some(Selection::Kind(ComponentKind::Fragment)),
+            _ => input
+                .split(',')
+                .map(|id| id.trim().parse().ok())
+                .collect::<Option<Vec<_>>>()
+                .map(Selection::Ids),
+        }
+    }
+
+    fn includes(&self, component: &Component) -> bool {
+        match self {
+            Selection::All => true,
+            Selection::Start => component.contains_start,
+            Selection::Kind(kind) => component.kind == *kind,
+            Selection::Ids(ids) => ids.contains(&component.id),
+        }
+    }
+}
+
+/// Every pipe component of a map, numbered in reading order of their first tile
+pub(crate) struct Components {
+    /// The map's pipes, with the start replaced by its pipe if the loop through it closes
+    pipes: Vec<Pipe>,
+    width: usize,
+    height: usize,
+    start: Coord,
+    pub(crate) components: Vec<Component>,
+}
+
+impl Components {
+    pub(crate) fn find(map: &Map) -> Components {
+        let mut pipes = map.pipes.clone();
+        if let Ok((first, last)) = map.resolve_start() {
+            pipes[map.start.linear_index()] = Pipe::from_directions(&first, &last);
+        }
+        let coord_at = |n: usize| Coord {
+            x: n % map.width,
+            y: n / map.width,
+            width: map.width,
+            height: map.height,
+        };
+
+        let links = (0..pipes.len())
+            .map(|n| linked_neighbours(&pipes, coord_at(n)))
+            .collect::<Vec<_>>();
+
+        let mut labelled = vec![false; pipes.len()];
+        let mut components = Vec::new();
+        for n in 0..pipes.len() {
+            if labelled[n] || pipes[n] == Pipe::Ground {
+                continue;
+            }
+
+            let mut members = vec![n];
+            let mut queue = VecDeque::from([n]);
+            labelled[n] = true;
+            while let Some(current) = queue.pop_front() {
+                for &next in links[current].iter() {
+                    if !labelled[next] {
+                        labelled[next] = true;
+                        members.push(next);
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            // A loop goes through each of its crossings twice, so must use all four sides
+            let is_crossing = |m: usize| pipes[m] == Pipe::Crossing && links[m].len() == 4;
+            let fully_linked = members
+                .iter()
+                .all(|&m| links[m].len() == 2 || is_crossing(m));
+            let n_crossings = members.iter().filter(|&&m| is_crossing(m)).count();
+            let loop_path = fully_linked
+                .then(|| walk_loop(&pipes, &links, coord_at(n)))
+                .flatten()
+                .filter(|path| path.len() == members.len() + n_crossings);
+
+            let is_chain = members.len() > 1
+                && members
+                    .iter()
+                    .all(|&m| links[m].len() <= 2 && pipes[m] != Pipe::Crossing);
+            // Valves can stop a ring of pipes being walked, which leaves it with no ends
+            let chain_end = members
+                .iter()
+                .find(|&&m| links[m].len() == 1)
+                .filter(|_| is_chain);
+            let (kind, tiles) = if let Some(path) = loop_path {
+                (ComponentKind::ClosedLoop, path)
+            } else if let Some(&end) = chain_end {
+                let chain = walk(&links, end);
+                (
+                    ComponentKind::OpenChain,
+                    chain.into_iter().map(coord_at).collect(),
+                )
+            } else {
+                let fragment = members.into_iter().map(coord_at).collect();
+                (ComponentKind::Fragment, fragment)
+            };
+
+            let enclosed = (kind == ComponentKind::ClosedLoop).then(|| {
+                enclosed_tiles(&tiles).unwrap_or_else(|| {
+                    fill::count_inside(&tiles, map.width, map.height, FillRule::EvenOdd)
+                })
+            });
+            components.push(Component {
+                id: components.len(),
+                kind,
+                enclosed,
+                contains_start: tiles.contains(&map.start),
+                tiles,
+            });
+        }
+
+        Components {
+            pipes,
+            width: map.width,
+            height: map.height,
+            start: map.start,
+            components,
+        }
+    }
+
+    pub(crate) fn count(&self, kind: ComponentKind) -> usize {
+        self.components
+            .iter()
+            .filter(|component| component.kind == kind)
+            .count()
+    }
+
+    /// The map in puzzle format, with every pipe outside the selected components replaced
+    /// by ground
+    pub(crate) fn cleaned(&self, selection: &Selection) -> String {
+        let mut chars = vec!['.'; self.pipes.len()];
+        for component in self.components.iter().filter(|c| selection.includes(c)) {
+            for coord in component.tiles.iter() {
+                chars[coord.linear_index()] = if *coord == self.start {
+                    'S'
+                } else {
+                    self.pipes[coord.linear_index()].to_input_char()
+                };
+            }
+        }
+
+        let mut output = String::with_capacity(self.pipes.len() + self.height);
+        for row in chars.chunks(self.width) {
+            output.extend(row);
+            output.push('\n');
+        }
+        output
+    }
+}
+
+impl Display for Components {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for component in self.components.iter() {
+            write!(
+                f,
+                "#{} {}, {} tiles",
+                component.id,
+                component.kind,
+                component.tiles.len()
+            )?;
+            if let Some(enclosed) = component.enclosed {
+                write!(f, ", {} enclosed", enclosed)?;
+            }
+            if component.contains_start {
+                write!(f, " (start)")?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(
+            f,
+            "{} closed loops, {} open chains, {} fragments",
+            self.count(ComponentKind::ClosedLoop),
+            self.count(ComponentKind::OpenChain),
+            self.count(ComponentKind::Fragment)
+        )
+    }
+}
+
+/// The linear indices of the neighbours that `coord` connects to. An unresolved start
+/// connects to every neighbour pointing at it.
+fn linked_neighbours(pipes: &[Pipe], coord: Coord) -> Vec<usize> {
+    let directions = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+    let pipe = pipes[coord.linear_index()];
+    let points_at =
+        |pipe: Pipe, direction: &Direction| pipe == Pipe::Start || pipe.contains(direction);
+
+    directions
+        .iter()
+        .filter(|direction| points_at(pipe, direction))
+        .filter_map(|direction| {
+            let next = coord.step(direction)?;
+            let linked = points_at(pipes[next.linear_index()], &direction.compliment());
+            linked.then(|| next.linear_index())
+        })
+        .collect()
+}
+
+/// Follow the pipes round from `from`, going straight through crossings, until coming back
+/// to it. `None` if the pipes do not lead back along links.
+fn walk_loop(pipes: &[Pipe], links: &[Vec<usize>], from: Coord) -> Option<Vec<Coord>> {
+    let directions = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+    let mut direction = directions
+        .into_iter()
+        .find(|d| pipes[from.linear_index()].contains(d))?;
+
+    let mut path = vec![from];
+    let mut coord = from;
+    for _ in 0..2 * pipes.len() {
+        let next = coord.step(&direction)?;
+        if !links[coord.linear_index()].contains(&next.linear_index()) {
+            return None;
+        }
+        if next == from {
+            return Some(path);
+        }
+        direction = pipes[next.linear_index()].other_end(&direction.compliment())?;
+        path.push(next);
+        coord = next;
+    }
+    None
+}
+
+/// Follow the links from `from` until reaching the other end of a chain
+fn walk(links: &[Vec<usize>], from: usize) -> Vec<usize> {
+    let mut path = vec![from];
+    let (mut previous, mut current) = (None, from);
+    while let Some(&next) = links[current].iter().find(|&&n| Some(n) != previous) {
+        if next == from {
+            break;
+        }
+        path.push(next);
+        (previous, current) = (Some(current), next);
+    }
+    path
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    const SAMPLE: &str = indoc! {"
+        .....F-7.
+        .S-7.|.|.
+        .|.|.L-J.
+        .L-J.....
+        .F--..|..
+    "};
+
+    #[test]
+    fn test_classify() {
+        let components = Components::find(&Map::parse_input(SAMPLE).unwrap());
+        let summary = components
+            .components
+            .iter()
+            .map(|c| (c.kind, c.tiles.len(), c.enclosed, c.contains_start))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (ComponentKind::ClosedLoop, 8, Some(1), false),
+                (ComponentKind::ClosedLoop, 8, Some(1), true),
+                (ComponentKind::OpenChain, 3, None, false),
+                (ComponentKind::Fragment, 1, None, false),
+            ]
+        );
+
+        // Chains are walked from one end to the other
+        let chain = &components.components[2].tiles;
+        assert_eq!((chain[0].x, chain[2].x), (1, 3));
+
+        let report = components.to_string();
+        assert!(report.contains("#1 closed loop, 8 tiles, 1 enclosed (start)\n"));
+        assert!(report.ends_with("2 closed loops, 1 open chains, 1 fragments\n"));
+    }
+
+    #[test]
+    fn test_unresolved_start() {
+        let components = Components::find(&Map::parse_input("S-.\n").unwrap());
+        assert_eq!(components.components.len(), 1);
+        assert_eq!(components.components[0].kind, ComponentKind::OpenChain);
+        assert!(components.components[0].contains_start);
+
+        // Opposing valves leave the start unresolved, and the ring has no ends to walk from
+        let components = Components::find(&Map::parse_input("S>7\n|.|\nL>J\n").unwrap());
+        assert_eq!(components.components.len(), 1);
+        assert_eq!(components.components[0].kind, ComponentKind::Fragment);
+        assert_eq!(components.components[0].tiles.len(), 8);
+        assert!(components.components[0].contains_start);
+    }
+
+    #[test]
+    fn test_cleaned() {
+        let components = Components::find(&Map::parse_input(SAMPLE).unwrap());
+        let expected = indoc! {"
+            .........
+            .S-7.....
+            .|.|.....
+            .L-J.....
+            .........
+        "};
+        assert_eq!(components.cleaned(&Selection::Start), expected);
+        assert_eq!(components.cleaned(&Selection::All), SAMPLE);
+
+        let expected = indoc! {"
+            .........
+            .........
+            .........
+            .........
+            .F--..|..
+        "};
+        assert_eq!(
+            components.cleaned(&Selection::parse("2, 3").unwrap()),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_selection() {
+        assert_eq!(Selection::parse("start"), Some(Selection::Start));
+        assert_eq!(
+            Selection::parse("chains"),
+            Some(Selection::Kind(ComponentKind::OpenChain))
+        );
+        assert_eq!(Selection::parse("1,4"), Some(Selection::Ids(vec![1, 4])));
+        assert_eq!(Selection::parse("loop"), None);
+    }
+
+    #[test]
+    fn test_crossings() {
+        let input = indoc! {"
+            S-----7...
+            |.F-7.|...
+            |.|.|.|.|.
+            |.L-+-J.|.
+            |...|..-+-
+            L---J...|.
+        "};
+        let components = Components::find(&Map::parse_input(input).unwrap());
+        let summary = components
+            .components
+            .iter()
+            .map(|c| (c.kind, c.tiles.len(), c.enclosed))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (ComponentKind::ClosedLoop, 30, Some(8)),
+                (ComponentKind::Fragment, 6, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_problem_loop() {
+        let components = Components::find(&Map::parse_input(include_str!("problem_text")).unwrap());
+        let start = components
+            .components
+            .iter()
+            .find(|c| c.contains_start)
+            .unwrap();
+        assert_eq!(start.kind, ComponentKind::ClosedLoop);
+        assert_eq!(start.tiles.len(), 2 * 6886);
+        assert_eq!(start.enclosed, Some(371));
+    }
+}
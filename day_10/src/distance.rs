@@ -0,0 +1,217 @@
+use crate::{Coord, PipeLoop};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// The number of steps along the loop from the start to every loop tile
+pub(crate) struct DistanceMap {
+    /// `None` for tiles that are not part of the loop
+    distances: Vec<Option<u32>>,
+    /// The largest distance, kept so that colouring a tile doesn't search the whole map
+    max_distance: u32,
+    width: usize,
+    height: usize,
+}
+
+impl DistanceMap {
+    /// Breadth first search from the start, following the pipes of the loop.
+    ///
+    /// The search is over the tile and the side it was entered from, so that it goes
+    /// straight through crossings, and valves are only passed the way they point.
+    pub(crate) fn new(pipe_loop: &PipeLoop) -> DistanceMap {
+        let map = &pipe_loop.map;
+        let mut distances = vec![None; map.pipes.len()];
+        distances[map.start.linear_index()] = Some(0);
+        let mut entered = vec![[false; 4]; map.pipes.len()];
+
+        let (a, b) = map
+            .get(&map.start)
+            .directions()
+            .expect("The start is replaced by its pipe");
+        let mut queue = VecDeque::from([(map.start, a, 0), (map.start, b, 0)]);
+        while let Some((coord, direction, distance)) = queue.pop_front() {
+            let Some(next) = coord.step(&direction) else {
+                continue;
+            };
+            let side = direction.compliment();
+            if next == map.start || entered[next.linear_index()][side as usize] {
+                continue;
+            }
+            entered[next.linear_index()][side as usize] = true;
+
+            // Valves cannot be entered from the side they point out of
+            if let Some(exit) = map.get(&next).exit(&side) {
+                distances[next.linear_index()].get_or_insert(distance + 1);
+                queue.push_back((next, exit, distance + 1));
+            }
+        }
+
+        DistanceMap {
+            max_distance: distances.iter().flatten().copied().max().unwrap_or(0),
+            distances,
+            width: map.width,
+            height: map.height,
+        }
+    }
+
+    pub(crate) fn get(&self, x: usize, y: usize) -> Option<u32> {
+        self.distances[y * self.width + x]
+    }
+
+    pub(crate) fn max_distance(&self) -> u32 {
+        self.max_distance
+    }
+
+    /// The `(x, y)` positions of the tiles furthest from the start, in reading order
+    pub(crate) fn farthest_tiles(&self) -> Vec<(usize, usize)> {
+        let max = self.max_distance();
+        self.distances
+            .iter()
+            .enumerate()
+            .filter(|(_, distance)| **distance == Some(max))
+            .map(|(n, _)| (n % self.width, n / self.width))
+            .collect()
+    }
+
+    /// Colour each loop tile by how far it is from the start, from blue at the start to
+    /// red at the far side
+    fn colour(&self, distance: u32) -> [u8; 3] {
+        let t = distance as f64 / self.max_distance.max(1) as f64;
+        let red = (255.0 * t).round() as u8;
+        let green = (255.0 * (1.0 - (2.0 * t - 1.0).abs())).round() as u8;
+        let blue = (255.0 * (1.0 - t)).round() as u8;
+        [red, green, blue]
+    }
+
+    /// Draw the loop with ANSI true colour escapes, other tiles are left blank
+    pub(crate) fn render_terminal(&self, pipe_loop: &PipeLoop) -> String {
+        let mut output = String::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                match self.get(x, y) {
+                    Some(distance) => {
+                        let [r, g, b] = self.colour(distance);
+                        let coord = Coord {
+                            x,
+                            y,
+                            width: self.width,
+                            height: self.height,
+                        };
+                        let pipe = pipe_loop.map.get(&coord);
+                        write!(output, "\x1b[38;2;{};{};{}m{}", r, g, b, pipe).unwrap();
+                    }
+                    None => output.push(' '),
+                }
+            }
+            output.push_str("\x1b[0m\n");
+        }
+        output
+    }
+
+    /// Write the heatmap as a binary PPM image, each tile drawn as a `scale` pixel square
+    pub(crate) fn write_ppm(&self, writer: &mut impl Write, scale: usize) -> std::io::Result<()> {
+        let (width, height) = (self.width * scale, self.height * scale);
+        write!(writer, "P6\n{} {}\n255\n", width, height)?;
+
+        let mut row = Vec::with_capacity(width * 3);
+        for y in 0..self.height {
+            row.clear();
+            for x in 0..self.width {
+                let colour = self.get(x, y).map_or([0, 0, 0], |d| self.colour(d));
+                for _ in 0..scale {
+                    row.extend_from_slice(&colour);
+                }
+            }
+            for _ in 0..scale {
+                writer.write_all(&row)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::trace_loop;
+    use indoc::indoc;
+
+    const COMPLEX: &str = indoc! {"
+        ..F7.
+        .FJ|.
+        SJ.L7
+        |F--J
+        LJ...
+    "};
+
+    #[test]
+    fn test_distances() {
+        let distances = DistanceMap::new(&trace_loop(COMPLEX).unwrap());
+
+        let expected = [
+            [None, None, Some(4), Some(5), None],
+            [None, Some(2), Some(3), Some(6), None],
+            [Some(0), Some(1), None, Some(7), Some(8)],
+            [Some(1), Some(4), Some(5), Some(6), Some(7)],
+            [Some(2), Some(3), None, None, None],
+        ];
+        for (y, row) in expected.iter().enumerate() {
+            for (x, distance) in row.iter().enumerate() {
+                assert_eq!(distances.get(x, y), *distance, "({}, {})", x, y);
+            }
+        }
+
+        assert_eq!(distances.max_distance(), 8);
+        assert_eq!(distances.farthest_tiles(), vec![(4, 2)]);
+    }
+
+    #[test]
+    fn test_matches_walk() {
+        let pipe_loop = trace_loop(include_str!("problem_text")).unwrap();
+        let distances = DistanceMap::new(&pipe_loop);
+        assert_eq!(distances.max_distance(), 6886);
+        assert_eq!(distances.farthest_tiles().len(), 1);
+    }
+
+    #[test]
+    fn test_crossing() {
+        let input = indoc! {"
+            S-----7
+            |.F-7.|
+            |.|.|.|
+            |.L-+-J
+            |...|..
+            L---J..
+        "};
+        let distances = DistanceMap::new(&trace_loop(input).unwrap());
+        // Reached from the east first, the turn at the crossing is not a shortcut
+        assert_eq!(distances.get(4, 3), Some(11));
+        assert_eq!(distances.get(4, 2), Some(12));
+
+        // A valve next to the start means the loop can only be walked the long way round
+        let distances = DistanceMap::new(&trace_loop(&input.replace("S-", "S<")).unwrap());
+        assert_eq!(distances.get(0, 1), Some(1));
+        assert_eq!(distances.get(1, 0), Some(29));
+        assert_eq!(distances.max_distance(), 29);
+    }
+
+    #[test]
+    fn test_render() {
+        let pipe_loop = trace_loop(COMPLEX).unwrap();
+        let distances = DistanceMap::new(&pipe_loop);
+
+        let terminal = distances.render_terminal(&pipe_loop);
+        assert_eq!(terminal.lines().count(), 5);
+        // The start is blue and the farthest tile is red
+        assert!(terminal.contains("\x1b[38;2;0;0;255m┌"));
+        assert!(terminal.contains("\x1b[38;2;255;0;0m┐"));
+
+        let mut image = Vec::new();
+        distances.write_ppm(&mut image, 2).unwrap();
+        let header = b"P6\n10 10\n255\n";
+        assert!(image.starts_with(header));
+        assert_eq!(image.len(), header.len() + 10 * 10 * 3);
+        // The top left tile is not part of the loop
+        assert_eq!(&image[header.len()..header.len() + 3], &[0, 0, 0]);
+    }
+}
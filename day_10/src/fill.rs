@@ -0,0 +1,163 @@
+use crate::{Coord, Map, State};
+
+/// How to decide whether a tile is inside a loop that may cross itself
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum FillRule {
+    /// Inside if a ray out of the tile crosses the loop an odd number of times
+    EvenOdd,
+    /// Inside if the loop winds around the tile at all
+    NonZero,
+}
+
+impl FillRule {
+    /// Parses `evenodd` or `nonzero`
+    pub(crate) fn from_name(name: &str) -> Option<FillRule> {
+        match name {
+            "evenodd" => Some(FillRule::EvenOdd),
+            "nonzero" => Some(FillRule::NonZero),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0,
+        }
+    }
+}
+
+/// The number of times the loop winds around each tile, clockwise positive, or `None` for
+/// the tiles of the loop itself.
+///
+/// A ray is cast east from just below the centre of each tile, so it crosses every
+/// vertical step of the loop between this row and the next, and never runs along one.
+pub(crate) fn winding_numbers(path: &[Coord], width: usize, height: usize) -> Vec<Option<i32>> {
+    let mut on_loop = vec![false; width * height];
+    let mut crossings = vec![0; width * height];
+    for (a, b) in path.iter().zip(path.iter().cycle().skip(1)) {
+        on_loop[a.linear_index()] = true;
+        if a.x == b.x {
+            let upper = if a.y < b.y { a } else { b };
+            crossings[upper.linear_index()] += if b.y > a.y { 1 } else { -1 };
+        }
+    }
+
+    let mut windings = vec![None; width * height];
+    for y in 0..height {
+        let mut winding = 0;
+        for x in (0..width).rev() {
+            let idx = y * width + x;
+            if !on_loop[idx] {
+                windings[idx] = Some(winding);
+            }
+            winding += crossings[idx];
+        }
+    }
+    windings
+}
+
+/// Each tile as a character, the loop drawn as `map` has it and the other tiles marked
+/// inside or outside under `rule`
+pub(crate) fn annotate<'a>(
+    path: &[Coord],
+    map: &'a Map,
+    rule: FillRule,
+) -> impl Iterator<Item = char> + 'a {
+    winding_numbers(path, map.width, map.height)
+        .into_iter()
+        .zip(map.pipes.iter())
+        .map(move |(winding, pipe)| match winding {
+            Some(winding) if rule.is_inside(winding) => State::Inside.to_char(),
+            Some(_) => State::Outside.to_char(),
+            None => pipe.to_char(),
+        })
+}
+
+/// The number of tiles inside the loop under `rule`
+pub(crate) fn count_inside(path: &[Coord], width: usize, height: usize, rule: FillRule) -> usize {
+    winding_numbers(path, width, height)
+        .into_iter()
+        .flatten()
+        .filter(|&winding| rule.is_inside(winding))
+        .count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{trace_loop, walk_map, MapError, Pipe};
+    use indoc::indoc;
+
+    /// The loop goes round the outside, then through the crossing and round the small
+    /// square in the top left again, so the middle of that square is wound twice
+    const DOUBLE_WOUND: &str = indoc! {"
+        S-----7
+        |.F-7.|
+        |.|.|.|
+        |.L-+-J
+        |...|..
+        L---J..
+    "};
+
+    #[test]
+    fn test_winding_numbers() {
+        let pipe_loop = trace_loop(DOUBLE_WOUND).unwrap();
+        let windings = winding_numbers(&pipe_loop.path, 7, 6);
+        let at = |x: usize, y: usize| windings[y * 7 + x];
+
+        assert_eq!(at(3, 2), Some(2));
+        assert_eq!(at(1, 1), Some(1));
+        assert_eq!(at(5, 4), Some(0));
+        assert_eq!(at(4, 3), None);
+
+        let (width, height) = (7, 6);
+        let even_odd = count_inside(&pipe_loop.path, width, height, FillRule::EvenOdd);
+        let non_zero = count_inside(&pipe_loop.path, width, height, FillRule::NonZero);
+        assert_eq!((even_odd, non_zero), (8, 9));
+    }
+
+    #[test]
+    fn test_annotate() {
+        let pipe_loop = trace_loop(DOUBLE_WOUND).unwrap();
+        let annotated = annotate(&pipe_loop.path, &pipe_loop.map, FillRule::EvenOdd)
+            .take(7 * 3)
+            .collect::<String>();
+        assert_eq!(annotated, "┌─────┐│I┌─┐I││I│ │I│");
+    }
+
+    #[test]
+    fn test_walk_crossing() {
+        // The walk goes through the crossing twice
+        let (steps, filtered_map) = walk_map(DOUBLE_WOUND).unwrap();
+        assert_eq!(steps, 15);
+        assert_eq!(filtered_map.pipes[3 * 7 + 4], Pipe::Crossing);
+
+        // A crossing only walked through once is kept as a straight pipe
+        let once = indoc! {"
+            .|...
+            S+-7.
+            ||.|.
+            L--J.
+        "};
+        let pipe_loop = trace_loop(once).unwrap();
+        assert_eq!(pipe_loop.map.pipes[5 + 1], Pipe::Horizontal);
+        assert_eq!(count_inside(&pipe_loop.path, 5, 4, FillRule::NonZero), 2);
+    }
+
+    #[test]
+    fn test_valves() {
+        // The loop has to be walked anticlockwise to get through the valve
+        let one_way = DOUBLE_WOUND.replace("L---J", "L->-J");
+        let pipe_loop = trace_loop(&one_way).unwrap();
+        assert_eq!(pipe_loop.path[1].y, 1);
+        assert_eq!(pipe_loop.path.len(), 30);
+
+        // Two valves facing each other can never both be passed
+        let blocked = one_way.replace("S-----7", "S-->--7");
+        assert!(matches!(
+            walk_map(&blocked).unwrap_err(),
+            MapError::DeadEnd { .. }
+        ));
+    }
+}
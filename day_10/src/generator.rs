@@ -0,0 +1,336 @@
+use crate::{Coord, Direction, Pipe};
+use xorshift::XorShift;
+
+/// A generated puzzle along with its answers, which are worked out from how the maze was
+/// built rather than by walking it
+#[derive(Debug)]
+pub(crate) struct Maze {
+    pub(crate) input: String,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) start: (usize, usize),
+    /// The pipe under `S`, which resolving the start has to find again
+    pub(crate) start_pipe: Pipe,
+    pub(crate) loop_length: usize,
+    pub(crate) farthest: u32,
+    pub(crate) inside: usize,
+}
+
+/// Generate a `width` by `height` maze whose loop through `S` is `loop_length` tiles long,
+/// with every other tile filled with junk.
+///
+/// The loop is the outline of a random blob of cells, where the corners of the cells are
+/// the tiles. The blob is grown one cell at a time, only adding cells that keep it free of
+/// holes and of cells touching only at a corner, so that its outline stays a simple loop.
+///
+/// Loops on a grid always have an even length, so `None` is returned for odd lengths, or
+/// if the loop does not fit.
+pub(crate) fn generate(width: usize, height: usize, loop_length: usize, seed: u64) -> Option<Maze> {
+    if loop_length < 4 || !loop_length.is_multiple_of(2) || width < 2 || height < 2 {
+        return None;
+    }
+    let mut rng = XorShift::new(seed);
+    let blob = Blob::grow(width - 1, height - 1, loop_length, &mut rng)?;
+
+    let mut pipes = vec![Pipe::Ground; width * height];
+    let mut outline = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            if let Some(pipe) = blob.outline_pipe(x, y) {
+                pipes[y * width + x] = pipe;
+                outline.push((x, y));
+            }
+        }
+    }
+    debug_assert_eq!(outline.len(), loop_length);
+    let start = outline[rng.below(outline.len())];
+    let start_pipe = pipes[start.1 * width + start.0];
+    pipes[start.1 * width + start.0] = Pipe::Start;
+
+    let is_outline = pipes
+        .iter()
+        .map(|&pipe| pipe != Pipe::Ground)
+        .collect::<Vec<_>>();
+    for (n, pipe) in pipes.iter_mut().enumerate() {
+        if !is_outline[n] {
+            *pipe = junk(&mut rng);
+        }
+    }
+
+    // Junk next to the start must not point at it, or it could be taken for the loop, except
+    // for at most one decoy. A loop through the start uses two of its sides, so following a
+    // single decoy can only dead end or wander, and the start resolution has to back out.
+    let decoy = rng.below(8);
+    let start_coord = Coord {
+        x: start.0,
+        y: start.1,
+        width,
+        height,
+    };
+    let directions = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+    for (n, direction) in directions.iter().enumerate() {
+        let Some(neighbour) = start_coord.step(direction) else {
+            continue;
+        };
+        let idx = neighbour.linear_index();
+        let wanted = n == decoy;
+        while !is_outline[idx] && pipes[idx].contains(&direction.compliment()) != wanted {
+            pipes[idx] = junk(&mut rng);
+        }
+    }
+
+    let input = pipes
+        .chunks(width)
+        .map(|row| {
+            row.iter()
+                .map(|pipe| pipe.to_input_char())
+                .collect::<String>()
+                + "\n"
+        })
+        .collect();
+
+    Some(Maze {
+        input,
+        width,
+        height,
+        start,
+        start_pipe,
+        loop_length,
+        farthest: (loop_length / 2) as u32,
+        inside: blob.enclosed_corners(),
+    })
+}
+
+/// A random tile, mostly pipes with some ground, like the puzzle input
+fn junk(rng: &mut XorShift) -> Pipe {
+    const JUNK: [Pipe; 8] = [
+        Pipe::Vertical,
+        Pipe::Horizontal,
+        Pipe::BendNE,
+        Pipe::BendNW,
+        Pipe::BendSW,
+        Pipe::BendSE,
+        Pipe::Ground,
+        Pipe::Ground,
+    ];
+    JUNK[rng.below(JUNK.len())]
+}
+
+/// A set of cells, where cell `(i, j)` is the square with corners at tiles `(i, j)` and
+/// `(i + 1, j + 1)`
+struct Blob {
+    cells: Vec<bool>,
+    width: usize,
+    height: usize,
+}
+
+impl Blob {
+    /// Grow a blob from a random cell until its outline is `perimeter` long
+    fn grow(width: usize, height: usize, perimeter: usize, rng: &mut XorShift) -> Option<Blob> {
+        let mut blob = Blob {
+            cells: vec![false; width * height],
+            width,
+            height,
+        };
+        let first = (rng.below(width), rng.below(height));
+        blob.cells[first.1 * width + first.0] = true;
+        let mut members = vec![first];
+        let mut current = 4;
+
+        let offsets = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+        let mut attempts = 0;
+        while current < perimeter {
+            attempts += 1;
+            if attempts > 1000 * perimeter {
+                return None;
+            }
+
+            let (i, j) = members[rng.below(members.len())];
+            let (di, dj) = offsets[rng.below(offsets.len())];
+            let (ci, cj) = (i as isize + di, j as isize + dj);
+            if blob.contains(ci, cj) || !blob.in_bounds(ci, cj) {
+                continue;
+            }
+
+            // Each neighbour already in the blob hides one side of the new cell, so a
+            // single neighbour makes the outline two longer, and two leave it the same.
+            // Three or more would shrink it, so never add those.
+            let neighbours = offsets
+                .iter()
+                .filter(|(oi, oj)| blob.contains(ci + oi, cj + oj))
+                .count();
+            let accept = match neighbours {
+                1 => true,
+                2 => rng.below(4) == 0,
+                _ => false,
+            };
+            if accept && blob.keeps_simple(ci, cj) {
+                blob.cells[cj as usize * width + ci as usize] = true;
+                members.push((ci as usize, cj as usize));
+                current = current + 4 - 2 * neighbours;
+            }
+        }
+        Some(blob)
+    }
+
+    fn in_bounds(&self, i: isize, j: isize) -> bool {
+        (0..self.width as isize).contains(&i) && (0..self.height as isize).contains(&j)
+    }
+
+    fn contains(&self, i: isize, j: isize) -> bool {
+        self.in_bounds(i, j) && self.cells[j as usize * self.width + i as usize]
+    }
+
+    /// Whether adding the cell leaves the blob without holes or corner-only contacts.
+    ///
+    /// Going round the eight cells surrounding it, the ones in the blob must form a single
+    /// run, and that run must not be a lone corner.
+    fn keeps_simple(&self, i: isize, j: isize) -> bool {
+        let ring = [
+            (0, -1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -1),
+        ];
+        let filled = ring.map(|(di, dj)| self.contains(i + di, j + dj));
+        let runs = (0..ring.len())
+            .filter(|&n| filled[n] && !filled[(n + ring.len() - 1) % ring.len()])
+            .count();
+        let lone_corner = filled.iter().filter(|&&f| f).count() == 1
+            && ring
+                .iter()
+                .zip(filled)
+                .any(|((di, dj), f)| f && *di != 0 && *dj != 0);
+
+        runs == 1 && !lone_corner
+    }
+
+    /// The pipe on the outline at tile `(x, y)`, if the outline goes through it. Each side
+    /// of a tile is a cell edge, which is on the outline if exactly one of the two cells
+    /// beside it is in the blob.
+    fn outline_pipe(&self, x: usize, y: usize) -> Option<Pipe> {
+        let (x, y) = (x as isize, y as isize);
+        let edge = |a: (isize, isize), b: (isize, isize)| {
+            self.contains(a.0, a.1) != self.contains(b.0, b.1)
+        };
+        let sides = [
+            (Direction::North, edge((x - 1, y - 1), (x, y - 1))),
+            (Direction::East, edge((x, y - 1), (x, y))),
+            (Direction::South, edge((x - 1, y), (x, y))),
+            (Direction::West, edge((x - 1, y - 1), (x - 1, y))),
+        ];
+        let mut open = sides.iter().filter(|(_, on)| *on).map(|(d, _)| d);
+        let (first, second) = (open.next()?, open.next()?);
+        Some(Pipe::from_directions(first, second))
+    }
+
+    /// The number of tiles strictly inside the outline, which are the corners with a blob
+    /// cell on all four sides
+    fn enclosed_corners(&self) -> usize {
+        let (width, height) = (self.width as isize, self.height as isize);
+        (1..height)
+            .flat_map(|y| (1..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| {
+                self.contains(x - 1, y - 1)
+                    && self.contains(x, y - 1)
+                    && self.contains(x - 1, y)
+                    && self.contains(x, y)
+            })
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::interior::{InteriorStrategy, Verification};
+    use crate::{annotate_inside, trace_loop, walk_map, Map, State};
+
+    #[test]
+    fn test_generate() {
+        let maze = generate(12, 8, 20, 7).unwrap();
+        assert_eq!(maze.input.lines().count(), 8);
+        assert!(maze.input.lines().all(|line| line.len() == 12));
+        assert_eq!(maze.input.matches('S').count(), 1);
+
+        let (x, y) = maze.start;
+        assert_eq!(maze.input.lines().nth(y).unwrap().chars().nth(x), Some('S'));
+        assert_eq!(maze.farthest, 10);
+
+        // The same seed gives the same maze
+        assert_eq!(generate(12, 8, 20, 7).unwrap().input, maze.input);
+        assert_ne!(generate(12, 8, 20, 8).unwrap().input, maze.input);
+    }
+
+    #[test]
+    fn test_impossible() {
+        assert!(generate(10, 10, 21, 1).is_none());
+        assert!(generate(10, 10, 2, 1).is_none());
+        // The longest outline of a blob in a 3x3 grid of cells is far shorter than this
+        assert!(generate(4, 4, 40, 1).is_none());
+    }
+
+    #[test]
+    fn test_answers_match() {
+        let mut decoys = 0;
+        for seed in 1..=200 {
+            let size = 5 + seed as usize % 20;
+            let length = 4 + 2 * (seed as usize % (size * 2));
+            let Some(maze) = generate(size, size, length, seed) else {
+                continue;
+            };
+
+            let (farthest, filtered_map) = walk_map(&maze.input).unwrap();
+            assert_eq!(farthest, maze.farthest, "seed {}", seed);
+            let inside = annotate_inside(&filtered_map)
+                .into_iter()
+                .filter(|&c| c == State::Inside.to_char())
+                .count();
+            assert_eq!(inside, maze.inside, "seed {}", seed);
+
+            let pipe_loop = trace_loop(&maze.input).unwrap();
+            let start = pipe_loop.map.start;
+            assert_eq!(pipe_loop.map.get(&start), maze.start_pipe, "seed {}", seed);
+            assert_eq!(pipe_loop.path.len(), maze.loop_length, "seed {}", seed);
+            let map = Map::parse_input(&maze.input).unwrap();
+            decoys += [
+                Direction::North,
+                Direction::East,
+                Direction::South,
+                Direction::West,
+            ]
+            .iter()
+            .filter_map(|d| Some((start.step(d)?, d)))
+            .filter(|(coord, d)| {
+                map.get(coord).contains(&d.compliment()) && pipe_loop.map.get(coord) == Pipe::Ground
+            })
+            .count();
+
+            let verification = Verification::run(&pipe_loop);
+            assert_eq!(verification.agreed(), Some(maze.inside), "seed {}", seed);
+        }
+        // Some of the starts have a decoy for the resolution to back out of
+        assert!(decoys > 0);
+    }
+
+    #[test]
+    fn test_large_maze() {
+        let maze = generate(140, 140, 12000, 2023).unwrap();
+        let pipe_loop = trace_loop(&maze.input).unwrap();
+        assert_eq!(pipe_loop.path.len(), 12000);
+        assert_eq!(
+            crate::interior::Scanline.count_inside(&pipe_loop),
+            Some(maze.inside),
+        );
+        assert_eq!(crate::map_inside(&pipe_loop.map), maze.inside);
+    }
+}
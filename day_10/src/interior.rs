@@ -0,0 +1,301 @@
+use crate::fill::{self, FillRule};
+use crate::{annotate_inside, Coord, PipeLoop, State};
+use std::collections::HashSet;
+use std::fmt::Display;
+
+/// Returns the strategy with the given name, one of `scanline`, `shoelace`, `flood`,
+/// `evenodd` or `nonzero`
+pub(crate) fn from_name(name: &str) -> Option<Box<dyn InteriorStrategy>> {
+    match name {
+        "scanline" => Some(Box::new(Scanline)),
+        "shoelace" => Some(Box::new(Shoelace)),
+        "flood" => Some(Box::new(UpscaledFlood)),
+        "evenodd" => Some(Box::new(Winding(FillRule::EvenOdd))),
+        "nonzero" => Some(Box::new(Winding(FillRule::NonZero))),
+        _ => None,
+    }
+}
+
+pub(crate) fn all_strategies() -> Vec<Box<dyn InteriorStrategy>> {
+    vec![
+        Box::new(Scanline),
+        Box::new(Shoelace),
+        Box::new(UpscaledFlood),
+        Box::new(Winding(FillRule::EvenOdd)),
+        Box::new(Winding(FillRule::NonZero)),
+    ]
+}
+
+/// A way of counting the tiles enclosed by the loop. Only `Scanline` and `Winding`
+/// handle loops that cross themselves, the others return `None` for them.
+pub(crate) trait InteriorStrategy {
+    fn name(&self) -> &'static str;
+
+    fn count_inside(&self, pipe_loop: &PipeLoop) -> Option<usize>;
+}
+
+/// Count wall crossings along each row, see `map_inside`
+pub(crate) struct Scanline;
+
+impl InteriorStrategy for Scanline {
+    fn name(&self) -> &'static str {
+        "scanline"
+    }
+
+    fn count_inside(&self, pipe_loop: &PipeLoop) -> Option<usize> {
+        let inside = annotate_inside(&pipe_loop.map)
+            .into_iter()
+            .filter(|&c| c == State::Inside.to_char())
+            .count();
+        Some(inside)
+    }
+}
+
+/// The shoelace formula gives the area enclosed by the centres of the loop tiles, and
+/// Pick's theorem turns that into the number of tiles strictly inside:
+/// `area = inside + boundary / 2 - 1`.
+pub(crate) struct Shoelace;
+
+impl InteriorStrategy for Shoelace {
+    fn name(&self) -> &'static str {
+        "shoelace"
+    }
+
+    fn count_inside(&self, pipe_loop: &PipeLoop) -> Option<usize> {
+        enclosed_tiles(&pipe_loop.path)
+    }
+}
+
+/// The number of tiles strictly inside a closed path of neighbouring tiles, or `None` if
+/// the path goes through a tile twice, since Pick's theorem only holds for simple loops
+pub(crate) fn enclosed_tiles(path: &[Coord]) -> Option<usize> {
+    if repeats_tile(path) {
+        return None;
+    }
+    let twice_area = path
+        .iter()
+        .zip(path.iter().cycle().skip(1))
+        .map(|(a, b)| a.x as i64 * b.y as i64 - b.x as i64 * a.y as i64)
+        .sum::<i64>()
+        .unsigned_abs() as usize;
+
+    (twice_area + 2)
+        .checked_sub(path.len())
+        .map(|twice_inside| twice_inside / 2)
+}
+
+/// Whether the path goes through any tile more than once, as at a crossing
+pub(crate) fn repeats_tile(path: &[Coord]) -> bool {
+    let mut seen = HashSet::with_capacity(path.len());
+    !path.iter().all(|coord| seen.insert(coord.linear_index()))
+}
+
+/// Flood fill the outside on a grid with twice the resolution, so that the gaps between
+/// neighbouring pipes become tiles that the fill can squeeze through.
+pub(crate) struct UpscaledFlood;
+
+impl InteriorStrategy for UpscaledFlood {
+    fn name(&self) -> &'static str {
+        "flood"
+    }
+
+    fn count_inside(&self, pipe_loop: &PipeLoop) -> Option<usize> {
+        // A loop that crosses itself walls off regions that are neither inside nor out
+        if repeats_tile(&pipe_loop.path) {
+            return None;
+        }
+
+        // Tile (x, y) becomes (2x + 1, 2y + 1), leaving a border the fill can go around
+        let width = 2 * pipe_loop.map.width + 1;
+        let height = 2 * pipe_loop.map.height + 1;
+        let mut blocked = vec![false; width * height];
+
+        let path = &pipe_loop.path;
+        for (a, b) in path.iter().zip(path.iter().cycle().skip(1)) {
+            blocked[(2 * a.y + 1) * width + 2 * a.x + 1] = true;
+            // The pipe joining two tiles of the loop
+            blocked[(a.y + b.y + 1) * width + a.x + b.x + 1] = true;
+        }
+
+        let mut outside = vec![false; width * height];
+        let mut stack = vec![(0usize, 0usize)];
+        outside[0] = true;
+        while let Some((x, y)) = stack.pop() {
+            let neighbours = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+            for (nx, ny) in neighbours {
+                if nx >= width || ny >= height {
+                    continue;
+                }
+                let idx = ny * width + nx;
+                if !blocked[idx] && !outside[idx] {
+                    outside[idx] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+
+        let (map_width, map_height) = (pipe_loop.map.width, pipe_loop.map.height);
+        let inside = (0..map_height)
+            .flat_map(|y| (0..map_width).map(move |x| (2 * y + 1) * width + 2 * x + 1))
+            .filter(|&idx| !blocked[idx] && !outside[idx])
+            .count();
+        Some(inside)
+    }
+}
+
+/// Sum the winding number of the loop around each tile, see `fill::winding_numbers`
+pub(crate) struct Winding(pub(crate) FillRule);
+
+impl InteriorStrategy for Winding {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            FillRule::EvenOdd => "evenodd",
+            FillRule::NonZero => "nonzero",
+        }
+    }
+
+    fn count_inside(&self, pipe_loop: &PipeLoop) -> Option<usize> {
+        let map = &pipe_loop.map;
+        Some(fill::count_inside(
+            &pipe_loop.path,
+            map.width,
+            map.height,
+            self.0,
+        ))
+    }
+}
+
+/// The counts from every strategy that can handle the loop, which should all be the same
+#[derive(Debug, PartialEq)]
+pub(crate) struct Verification {
+    pub(crate) counts: Vec<(&'static str, usize)>,
+}
+
+impl Verification {
+    pub(crate) fn run(pipe_loop: &PipeLoop) -> Verification {
+        let counts = all_strategies()
+            .iter()
+            .filter_map(|strategy| Some((strategy.name(), strategy.count_inside(pipe_loop)?)))
+            .collect();
+        Verification { counts }
+    }
+
+    /// The count if every strategy agrees
+    pub(crate) fn agreed(&self) -> Option<usize> {
+        let (_, first) = self.counts.first()?;
+        self.counts
+            .iter()
+            .all(|(_, count)| count == first)
+            .then_some(*first)
+    }
+}
+
+impl Display for Verification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, count) in self.counts.iter() {
+            writeln!(f, "{:<10} {}", name, count)?;
+        }
+        match self.agreed() {
+            Some(count) => writeln!(f, "All strategies agree on {}", count),
+            None => writeln!(f, "Strategies disagree!"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::trace_loop;
+    use indoc::indoc;
+
+    const COMPLEX: &str = indoc! {"
+        ..F7.
+        .FJ|.
+        SJ.L7
+        |F--J
+        LJ...
+    "};
+
+    const SQUEEZE: &str = indoc! {"
+        ..........
+        .S------7.
+        .|F----7|.
+        .||....||.
+        .||....||.
+        .|L-7F-J|.
+        .|..||..|.
+        .L--JL--J.
+        ..........
+    "};
+
+    const NIGHTMARE: &str = indoc! {"
+        FF7FSF7F7F7F7F7F---7
+        L|LJ||||||||||||F--J
+        FL-7LJLJ||||||LJL-77
+        F--JF--7||LJLJ7F7FJ-
+        L---JF-JLJ.||-FJLJJ7
+        |F|F-JF---7F7-L7L|7|
+        |FFJF7L7F-JF7|JL---7
+        7-L-JL7||F7|L7F-7F7|
+        L.L7LFJ|||||FJL7||LJ
+        L7JLJL-JLJLJL--JLJ.L
+    "};
+
+    #[test]
+    fn test_strategies_agree() {
+        for (input, expected) in [(COMPLEX, 1), (SQUEEZE, 4), (NIGHTMARE, 10)] {
+            let pipe_loop = trace_loop(input).unwrap();
+            for strategy in all_strategies() {
+                assert_eq!(
+                    strategy.count_inside(&pipe_loop),
+                    Some(expected),
+                    "{}",
+                    strategy.name()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_figure_eight() {
+        let input = indoc! {"
+            S-7..
+            |.|..
+            L-+-7
+            ..|.|
+            ..L-J
+        "};
+        let pipe_loop = trace_loop(input).unwrap();
+        assert!(repeats_tile(&pipe_loop.path));
+
+        assert_eq!(Shoelace.count_inside(&pipe_loop), None);
+        assert_eq!(UpscaledFlood.count_inside(&pipe_loop), None);
+        for name in ["scanline", "evenodd", "nonzero"] {
+            let strategy = from_name(name).unwrap();
+            assert_eq!(strategy.count_inside(&pipe_loop), Some(2), "{}", name);
+        }
+
+        // Only the strategies that handle crossings take part
+        let verification = Verification::run(&pipe_loop);
+        assert_eq!(verification.counts.len(), 3);
+        assert_eq!(verification.agreed(), Some(2));
+    }
+
+    #[test]
+    fn test_verification() {
+        let verification = Verification::run(&trace_loop(include_str!("problem_text")).unwrap());
+        assert_eq!(verification.counts.len(), 5);
+        assert_eq!(verification.agreed(), Some(371));
+
+        let disagreement = Verification {
+            counts: vec![("scanline", 4), ("shoelace", 5)],
+        };
+        assert_eq!(disagreement.agreed(), None);
+        assert!(disagreement.to_string().contains("disagree"));
+    }
+}
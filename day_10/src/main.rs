@@ -1,57 +1,296 @@
 #![allow(dead_code)]
+mod components;
+mod distance;
+mod fill;
+mod generator;
+mod interior;
+
+use components::{Components, Selection};
+use distance::DistanceMap;
+use fill::FillRule;
+use interior::Verification;
 use itertools::Itertools;
 use std::cmp::{max, min};
 use std::fmt::Display;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [command, rest @ ..] = args.as_slice() {
+        if command == "inside" {
+            let strategy = rest.first().map_or("verify", String::as_str);
+            count_inside(strategy, rest.get(1).map(String::as_str));
+            return;
+        }
+        if command == "components" {
+            show_components(rest.first().map(String::as_str));
+            return;
+        }
+        if command == "generate" {
+            generate_maze(rest);
+            return;
+        }
+        if command == "stress" {
+            stress(rest);
+            return;
+        }
+        if command == "distance" {
+            show_distances(rest.first().map(String::as_str));
+            return;
+        }
+    }
+
     problem_one();
     problem_two();
 }
 
 fn problem_one() {
     let input = include_str!("problem_text");
-    let (steps, _) = walk_map(input);
-
-    println!("Problem one: {steps}");
+    match walk_map(input) {
+        Ok((steps, _)) => println!("Problem one: {steps}"),
+        Err(e) => println!("Error: {e}"),
+    }
 }
 
 fn problem_two() {
     let input = include_str!("problem_text");
-    let (_, filtered_map) = walk_map(input);
+    match walk_map(input) {
+        Ok((_, filtered_map)) => {
+            let inside_count = map_inside(&filtered_map);
+            println!("Problem two: {inside_count}");
+        }
+        Err(e) => println!("Error: {e}"),
+    }
+}
+
+/// Print a generated maze, `generate <width> <height> <loop length> [seed]`, with its
+/// answers on stderr so that the maze can be saved and fed back in
+fn generate_maze(args: &[String]) {
+    let numbers = args
+        .iter()
+        .map(|arg| arg.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>();
+    let (width, height, length, seed) = match numbers.as_deref() {
+        Ok([width, height, length]) => (*width, *height, *length, 1),
+        Ok([width, height, length, seed]) => (*width, *height, *length, *seed),
+        _ => {
+            eprintln!("Usage: generate <width> <height> <loop length> [seed]");
+            return;
+        }
+    };
 
-    let inside_count = map_inside(&filtered_map);
-    println!("Problem two: {inside_count}");
+    match generator::generate(width as usize, height as usize, length as usize, seed) {
+        Some(maze) => {
+            print!("{}", maze.input);
+            eprintln!("Farthest distance: {}", maze.farthest);
+            eprintln!("Inside: {}", maze.inside);
+        }
+        None => eprintln!("Cannot fit a loop of length {} on the grid", length),
+    }
 }
 
-fn walk_map(input: &str) -> (u32, Map) {
-    let map = Map::parse_input(input);
-    let mut steps = 1;
+/// Check the solvers against generated mazes, `stress [mazes] [size] [seed]`
+fn stress(args: &[String]) {
+    let arg = |n: usize, default: u64| args.get(n).and_then(|a| a.parse().ok()).unwrap_or(default);
+    let (n_mazes, size, seed) = (arg(0, 100), arg(1, 140) as usize, arg(2, 1));
+    if size < 2 {
+        eprintln!("Usage: stress [mazes] [size] [seed], where the size is at least 2");
+        return;
+    }
 
-    let mut filtered_map = map.empty_like();
-    let (mut new_direction, mut new_coord, mut new_pipe) = map.get_first_step();
-    let first_direction = new_direction;
+    let mut rng = xorshift::XorShift::new(seed);
+    let (mut checked, mut failures) = (0, 0);
+    let timer = std::time::Instant::now();
+    for _ in 0..n_mazes {
+        let maze_seed = rng.next_u64();
+        // Up to about as long as the puzzle input's loop on a full size grid
+        let length = 4 + 2 * rng.below(size * size / 3);
+        let Some(maze) = generator::generate(size, size, length, maze_seed) else {
+            continue;
+        };
+        checked += 1;
+
+        let walked = walk_map(&maze.input).map(|(farthest, filtered_map)| {
+            let inside = annotate_inside(&filtered_map)
+                .into_iter()
+                .filter(|&c| c == State::Inside.to_char())
+                .count();
+            let start_pipe = filtered_map.get(&filtered_map.start);
+            (farthest, inside, start_pipe)
+        });
+        let expected = (maze.farthest, maze.inside, maze.start_pipe);
+        if walked != Ok(expected) {
+            failures += 1;
+            println!(
+                "Seed {}: expected {:?}, got {:?}",
+                maze_seed, expected, walked
+            );
+        }
+    }
+    println!(
+        "Checked {} mazes in {:.2?}, {} failed",
+        checked,
+        timer.elapsed(),
+        failures
+    );
+}
 
-    loop {
-        filtered_map.set(&new_coord, new_pipe);
+/// Report every pipe component, or print the map keeping only the selected components
+fn show_components(selection: Option<&str>) {
+    let map = match Map::parse_input(include_str!("problem_text")) {
+        Ok(map) => map,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
+    let components = Components::find(&map);
+
+    match selection.map(|s| (s, Selection::parse(s))) {
+        None => print!("{}", components),
+        Some((_, Some(selection))) => print!("{}", components.cleaned(&selection)),
+        Some((s, None)) => eprintln!(
+            "Unknown selection {}, expected all, start, loops, chains, fragments or ids",
+            s
+        ),
+    }
+}
 
-        new_direction = new_pipe
-            .exit(&new_direction.compliment())
-            .expect("Unable to exit pipe");
-        new_coord = new_coord
-            .step(&new_direction)
-            .expect("Attempting to step off map");
-        new_pipe = map.get(&new_coord);
-        steps += 1;
+/// Print the loop coloured by distance from the start, and optionally save it as an image
+fn show_distances(image_path: Option<&str>) {
+    let pipe_loop = match trace_loop(include_str!("problem_text")) {
+        Ok(pipe_loop) => pipe_loop,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
+    let distances = DistanceMap::new(&pipe_loop);
+    print!("{}", distances.render_terminal(&pipe_loop));
+
+    let farthest = distances.farthest_tiles();
+    println!(
+        "Farthest distance {} at {}",
+        distances.max_distance(),
+        farthest
+            .iter()
+            .map(|(x, y)| format!("({}, {})", x, y))
+            .join(", ")
+    );
+
+    if let Some(path) = image_path {
+        let result = std::fs::File::create(path)
+            .map(std::io::BufWriter::new)
+            .and_then(|mut file| distances.write_ppm(&mut file, 4));
+        match result {
+            Ok(()) => println!("Wrote heatmap to {}", path),
+            Err(e) => eprintln!("Failed to write {}: {}", path, e),
+        }
+    }
+}
 
-        if new_pipe == Pipe::Start {
-            let replacement_start =
-                Pipe::from_directions(&new_direction.compliment(), &first_direction);
-            filtered_map.set(&new_coord, replacement_start);
-            break;
+/// Count the enclosed tiles with the named strategy, or with every strategy for `verify`.
+/// The fill rules `evenodd` and `nonzero` also draw the map, like `map_inside`.
+fn count_inside(strategy: &str, input_path: Option<&str>) {
+    let input = match input_path.map(std::fs::read_to_string) {
+        None => include_str!("problem_text").to_string(),
+        Some(Ok(input)) => input,
+        Some(Err(e)) => {
+            eprintln!("Failed to read {}: {}", input_path.unwrap_or_default(), e);
+            return;
+        }
+    };
+    let pipe_loop = match trace_loop(&input) {
+        Ok(pipe_loop) => pipe_loop,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
         }
+    };
+    if strategy == "verify" {
+        print!("{}", Verification::run(&pipe_loop));
+        return;
+    }
+    if let Some(rule) = FillRule::from_name(strategy) {
+        let inside_count = map_inside_with_rule(&pipe_loop, rule);
+        println!("{}: {}", strategy, inside_count);
+        return;
+    }
+
+    let Some(strategy) = interior::from_name(strategy) else {
+        eprintln!(
+            "Unknown strategy {}, expected one of: scanline, shoelace, flood, evenodd, \
+             nonzero, verify",
+            strategy
+        );
+        return;
+    };
+    match strategy.count_inside(&pipe_loop) {
+        Some(inside_count) => println!("{}: {}", strategy.name(), inside_count),
+        None => eprintln!(
+            "{} cannot count inside a loop that crosses itself",
+            strategy.name()
+        ),
     }
+}
+
+fn walk_map(input: &str) -> Result<(u32, Map), MapError> {
+    let pipe_loop = trace_loop(input)?;
+    let steps = pipe_loop.path.len() as u32;
+
+    Ok((get_farthest_distance(steps), pipe_loop.map))
+}
 
-    (get_farthest_distance(steps), filtered_map)
+/// The loop through the start tile
+struct PipeLoop {
+    /// A copy of the map with only the loop, and the start replaced by its pipe
+    map: Map,
+    /// Every tile of the loop in walking order, beginning at the start
+    path: Vec<Coord>,
+}
+
+fn trace_loop(input: &str) -> Result<PipeLoop, MapError> {
+    Map::parse_input(input)?.trace_loop()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum MapError {
+    InvalidCharacter {
+        character: char,
+        x: usize,
+        y: usize,
+    },
+    /// A row with a different width to the first row
+    RaggedRow {
+        y: usize,
+        expected: usize,
+        found: usize,
+    },
+    NoStart,
+    MultipleStarts,
+    /// The walk from the start reached a tile it cannot leave, or walked off the map
+    DeadEnd {
+        x: usize,
+        y: usize,
+    },
+    /// Fewer than two neighbours of the start connect back to it
+    UnclosedLoop,
+}
+
+impl Display for MapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapError::InvalidCharacter { character, x, y } => {
+                write!(f, "Invalid character {:?} at ({}, {})", character, x, y)
+            }
+            MapError::RaggedRow { y, expected, found } => {
+                write!(f, "Row {} has {} tiles, expected {}", y, found, expected)
+            }
+            MapError::NoStart => write!(f, "No start tile found"),
+            MapError::MultipleStarts => write!(f, "More than one start tile found"),
+            MapError::DeadEnd { x, y } => write!(f, "The loop dead ends at ({}, {})", x, y),
+            MapError::UnclosedLoop => write!(f, "The start tile is not part of a closed loop"),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -72,6 +311,7 @@ impl State {
         }
     }
 
+    #[allow(clippy::wrong_self_convention)]
     fn to_char(&self) -> char {
         use State::{Inside, Outside, Wall};
 
@@ -99,7 +339,31 @@ enum WallHistory {
 ///
 /// This also pretty prints the map with the inside regions annotated.
 fn map_inside(filtered_map: &Map) -> usize {
-    use State::Wall;
+    print_inside(&annotate_inside(filtered_map), filtered_map.width)
+}
+
+/// Like `map_inside`, but deciding which tiles are inside a loop that may cross itself
+/// with the given rule, see `fill::winding_numbers`
+fn map_inside_with_rule(pipe_loop: &PipeLoop, rule: FillRule) -> usize {
+    let outside_map = fill::annotate(&pipe_loop.path, &pipe_loop.map, rule).collect::<Vec<_>>();
+    print_inside(&outside_map, pipe_loop.map.width)
+}
+
+/// Print the annotated map and count the tiles marked inside
+fn print_inside(outside_map: &[char], width: usize) -> usize {
+    for line in outside_map.chunks(width) {
+        println!("{}", line.iter().join(""));
+    }
+
+    outside_map
+        .iter()
+        .filter(|c| **c == State::Inside.to_char())
+        .count()
+}
+
+/// Run the wall transition state machine over the map, returning each tile as a
+/// character: the pipe for loop tiles, otherwise whether it is inside or outside.
+fn annotate_inside(filtered_map: &Map) -> Vec<char> {
     let mut outside_map: Vec<char> = Vec::with_capacity(filtered_map.pipes.len());
     let mut history = WallHistory::None;
 
@@ -107,7 +371,13 @@ fn map_inside(filtered_map: &Map) -> usize {
         let mut state = State::Outside;
         for pipe in row {
             match (pipe, history) {
-                (Pipe::Vertical, _) => {
+                (Pipe::Vertical | Pipe::Valve(Direction::North | Direction::South), _) => {
+                    state = state.flip().unwrap();
+                    outside_map.push(pipe.to_char());
+                }
+                (Pipe::Crossing, _) => {
+                    // The vertical strand crosses the row, the horizontal one carries on
+                    // along any wall
                     state = state.flip().unwrap();
                     outside_map.push(pipe.to_char());
                 }
@@ -144,7 +414,9 @@ fn map_inside(filtered_map: &Map) -> usize {
                     outside_map.push(pipe.to_char());
                     history = WallHistory::None;
                 }
-                (Pipe::Horizontal, _) => outside_map.push(pipe.to_char()),
+                (Pipe::Horizontal | Pipe::Valve(Direction::East | Direction::West), _) => {
+                    outside_map.push(pipe.to_char())
+                }
                 (Pipe::Ground, _) => outside_map.push(state.to_char()),
                 (Pipe::Start, _) => {
                     panic!("Start pipe found in filtered map")
@@ -153,17 +425,11 @@ fn map_inside(filtered_map: &Map) -> usize {
         }
     }
 
-    for line in outside_map.chunks(filtered_map.width) {
-        println!("{}", line.iter().join(""));
-    }
-
     outside_map
-        .iter()
-        .filter(|c| **c == State::Inside.to_char())
-        .count()
 }
 
 /// The furthest point is simply the ceiling of half the number of steps
+#[allow(clippy::manual_div_ceil)]
 fn get_farthest_distance(steps: u32) -> u32 {
     (steps + 1) / 2
 }
@@ -187,6 +453,17 @@ impl Direction {
             West => East,
         }
     }
+
+    fn arrow(&self) -> char {
+        use Direction::{East, North, South, West};
+
+        match self {
+            North => '↑',
+            East => '→',
+            South => '↓',
+            West => '←',
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -264,6 +541,7 @@ impl Coord {
     }
 }
 
+#[derive(Debug)]
 struct Map {
     pipes: Vec<Pipe>,
     height: usize,
@@ -291,53 +569,79 @@ impl Map {
         self.pipes[coord.linear_index()]
     }
 
-    fn parse_input(input: &str) -> Map {
-        let pipes: Vec<_> = input
-            .lines()
-            .flat_map(|line| line.chars().map(Pipe::from_char))
-            .collect();
-        let n_pipes = pipes.len();
-
-        // This is a bit of an awkward way of getting the number of chars in the array
-        let width = input
-            .lines()
-            .next()
-            .unwrap()
-            .char_indices()
-            .map(|(i, _)| i)
-            .last()
-            .unwrap()
-            + 1;
-        let height = n_pipes / width;
-
-        let start = pipes
-            .iter()
-            .enumerate()
-            .find_map(|(n, p)| {
-                if *p == Pipe::Start {
-                    let y = n / width;
-                    let x = n % width;
-                    Some(Coord {
+    fn parse_input(input: &str) -> Result<Map, MapError> {
+        let mut pipes = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+
+        // Blank lines are skipped, so positions count only the rows that are kept
+        for line in input.lines().map(str::trim_end) {
+            if line.is_empty() {
+                continue;
+            }
+            let y = height;
+
+            let row = line
+                .chars()
+                .enumerate()
+                .map(|(x, character)| {
+                    Pipe::try_from_char(character).ok_or(MapError::InvalidCharacter {
+                        character,
                         x,
                         y,
-                        width,
-                        height,
                     })
-                } else {
-                    None
-                }
-            })
-            .expect("No start tile found");
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let expected = *width.get_or_insert(row.len());
+            if row.len() != expected {
+                return Err(MapError::RaggedRow {
+                    y,
+                    expected,
+                    found: row.len(),
+                });
+            }
+            pipes.extend(row);
+            height += 1;
+        }
+        let width = width.unwrap_or(0);
 
-        Map {
+        let mut starts = pipes.iter().positions(|p| *p == Pipe::Start);
+        let n = starts.next().ok_or(MapError::NoStart)?;
+        if starts.next().is_some() {
+            return Err(MapError::MultipleStarts);
+        }
+        let start = Coord {
+            x: n % width,
+            y: n / width,
+            width,
+            height,
+        };
+
+        Ok(Map {
             pipes,
             height,
             width,
             start,
-        }
+        })
     }
 
-    fn get_first_step(&self) -> (Direction, Coord, Pipe) {
+    /// The first step of the loop from the start, see `resolve_start`
+    fn get_first_step(&self) -> Result<(Direction, Coord, Pipe), MapError> {
+        let (direction, _) = self.resolve_start()?;
+        let coord = self
+            .start
+            .step(&direction)
+            .expect("Resolved steps are on the map");
+
+        Ok((direction, coord, self.get(&coord)))
+    }
+
+    /// Find the two directions out of the start that form the loop.
+    ///
+    /// Several neighbours may point at the start, so the walk out of each of them is
+    /// followed until it either comes back to the start, giving the pair, or dead ends.
+    fn resolve_start(&self) -> Result<(Direction, Direction), MapError> {
         let directions = [
             Direction::North,
             Direction::East,
@@ -345,27 +649,97 @@ impl Map {
             Direction::West,
         ];
 
-        let coord = self.start;
-        let (direction, new_coord, new_pipe) = directions
-            .iter()
-            .find_map(|d| {
-                let coord_step = coord.step(d)?;
-                let step_pipe = self.get(&coord_step);
-
-                let d_compliment = d.compliment();
-                if step_pipe.contains(&d_compliment) {
-                    Some((d, coord_step, step_pipe))
-                } else {
-                    None
-                }
+        let candidates = directions
+            .into_iter()
+            .filter(|d| {
+                self.start
+                    .step(d)
+                    .is_some_and(|coord| self.get(&coord).contains(&d.compliment()))
             })
-            .expect("Unable to find first step");
+            .collect::<Vec<_>>();
+        if candidates.len() < 2 {
+            return Err(MapError::UnclosedLoop);
+        }
 
-        (*direction, new_coord, new_pipe)
+        let mut first_error = None;
+        for &direction in candidates.iter() {
+            match self.follow_from_start(direction) {
+                Ok(returned) => return Ok((direction, returned)),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        Err(first_error.expect("There are candidates"))
+    }
+
+    /// Walk out of the start in the given direction and return the side of the start that
+    /// the walk comes back into
+    fn follow_from_start(&self, first_direction: Direction) -> Result<Direction, MapError> {
+        let dead_end = |coord: &Coord| MapError::DeadEnd {
+            x: coord.x,
+            y: coord.y,
+        };
+
+        let mut coord = self.start;
+        let mut direction = first_direction;
+        // A walk that does not come back must dead end before visiting every tile, or every
+        // crossing twice
+        for _ in 0..2 * self.pipes.len() {
+            let next = coord.step(&direction).ok_or_else(|| dead_end(&coord))?;
+            let pipe = self.get(&next);
+            if pipe == Pipe::Start {
+                return Ok(direction.compliment());
+            }
+
+            direction = pipe
+                .exit(&direction.compliment())
+                .ok_or_else(|| dead_end(&next))?;
+            coord = next;
+        }
+        Err(MapError::UnclosedLoop)
+    }
+
+    /// Walk the loop through the start, keeping only its tiles
+    fn trace_loop(&self) -> Result<PipeLoop, MapError> {
+        let (first_direction, last_side) = self.resolve_start()?;
+        let mut filtered_map = self.empty_like();
+        filtered_map.set(
+            &self.start,
+            Pipe::from_directions(&first_direction, &last_side),
+        );
+
+        let mut path = vec![self.start];
+        let mut direction = first_direction;
+        let mut coord = self
+            .start
+            .step(&direction)
+            .expect("Resolved steps are on the map");
+        while coord != self.start {
+            let pipe = self.get(&coord);
+            let side = direction.compliment();
+            direction = pipe.exit(&side).expect("Resolved loops are closed");
+
+            // A crossing only used in one axis is a straight pipe as far as the loop goes
+            let kept = match (pipe, filtered_map.get(&coord)) {
+                (Pipe::Crossing, Pipe::Ground) => Pipe::from_directions(&side, &direction),
+                _ => pipe,
+            };
+            filtered_map.set(&coord, kept);
+            path.push(coord);
+
+            coord = coord.step(&direction).expect("Resolved loops are closed");
+        }
+
+        Ok(PipeLoop {
+            map: filtered_map,
+            path,
+        })
     }
 }
 
 impl Display for Map {
+    #[allow(clippy::unnecessary_cast)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for line in self.pipes.chunks(self.width as usize) {
             writeln!(
@@ -388,23 +762,33 @@ enum Pipe {
     BendSE,
     Ground,
     Start,
+    /// Goes straight through in both axes, so a loop can cross itself
+    Crossing,
+    /// A straight pipe that can only be walked in the given direction
+    Valve(Direction),
 }
 
 impl Pipe {
-    fn from_char(input: char) -> Pipe {
+    fn try_from_char(input: char) -> Option<Pipe> {
         match input {
-            '|' => Pipe::Vertical,
-            '-' => Pipe::Horizontal,
-            'L' => Pipe::BendNE,
-            'J' => Pipe::BendNW,
-            '7' => Pipe::BendSW,
-            'F' => Pipe::BendSE,
-            '.' => Pipe::Ground,
-            'S' => Pipe::Start,
-            _ => panic!("Unexpected character for pipe."),
+            '|' => Some(Pipe::Vertical),
+            '-' => Some(Pipe::Horizontal),
+            'L' => Some(Pipe::BendNE),
+            'J' => Some(Pipe::BendNW),
+            '7' => Some(Pipe::BendSW),
+            'F' => Some(Pipe::BendSE),
+            '.' => Some(Pipe::Ground),
+            'S' => Some(Pipe::Start),
+            '+' => Some(Pipe::Crossing),
+            '^' => Some(Pipe::Valve(Direction::North)),
+            '>' => Some(Pipe::Valve(Direction::East)),
+            'v' => Some(Pipe::Valve(Direction::South)),
+            '<' => Some(Pipe::Valve(Direction::West)),
+            _ => None,
         }
     }
 
+    #[allow(clippy::wrong_self_convention)]
     fn to_char(&self) -> char {
         match self {
             Pipe::Vertical => '│',
@@ -415,15 +799,39 @@ impl Pipe {
             Pipe::BendSE => '┌',
             Pipe::Ground => '.',
             Pipe::Start => 'S',
+            Pipe::Crossing => '┼',
+            Pipe::Valve(direction) => direction.arrow(),
+        }
+    }
+
+    /// The character used for this pipe in the puzzle input
+    fn to_input_char(self) -> char {
+        match self {
+            Pipe::Vertical => '|',
+            Pipe::Horizontal => '-',
+            Pipe::BendNE => 'L',
+            Pipe::BendNW => 'J',
+            Pipe::BendSW => '7',
+            Pipe::BendSE => 'F',
+            Pipe::Ground => '.',
+            Pipe::Start => 'S',
+            Pipe::Crossing => '+',
+            Pipe::Valve(Direction::North) => '^',
+            Pipe::Valve(Direction::East) => '>',
+            Pipe::Valve(Direction::South) => 'v',
+            Pipe::Valve(Direction::West) => '<',
         }
     }
 
+    /// The two ends of the pipe, or `None` for tiles that do not have exactly two
     fn directions(&self) -> Option<(Direction, Direction)> {
         use Direction::{East, North, South, West};
 
         match self {
             Pipe::Ground => None,
             Pipe::Start => None,
+            Pipe::Crossing => None,
+            Pipe::Valve(direction) => Some((direction.compliment(), *direction)),
             Pipe::Vertical => Some((North, South)),
             Pipe::Horizontal => Some((East, West)),
             Pipe::BendNE => Some((North, East)),
@@ -433,8 +841,20 @@ impl Pipe {
         }
     }
 
-    /// Given a direction, return the other direction of the pipe
+    /// Given the side the pipe is entered from, return the side it is left by, or `None`
+    /// if the pipe cannot be entered from that side
     fn exit(&self, direction: &Direction) -> Option<Direction> {
+        match self {
+            Pipe::Valve(flow) if *direction != flow.compliment() => None,
+            _ => self.other_end(direction),
+        }
+    }
+
+    /// Like `exit`, but ignoring which way valves let the walk go
+    fn other_end(&self, direction: &Direction) -> Option<Direction> {
+        if *self == Pipe::Crossing {
+            return Some(direction.compliment());
+        }
         let direction_pairs = self.directions()?;
 
         if direction_pairs.0 == *direction {
@@ -442,13 +862,15 @@ impl Pipe {
         } else if direction_pairs.1 == *direction {
             Some(direction_pairs.0)
         } else {
-            panic!("Given start direction is invalid.")
+            None
         }
     }
 
     /// Can this pipe be entered from the given direction
     fn contains(&self, direction: &Direction) -> bool {
-        if let Some(direction_pairs) = self.directions() {
+        if *self == Pipe::Crossing {
+            true
+        } else if let Some(direction_pairs) = self.directions() {
             direction_pairs.0 == *direction || direction_pairs.1 == *direction
         } else {
             false
@@ -483,6 +905,8 @@ impl Display for Pipe {
             Pipe::BendSE => write!(f, "┌"),
             Pipe::Ground => write!(f, "·"),
             Pipe::Start => write!(f, "S"),
+            Pipe::Crossing => write!(f, "┼"),
+            Pipe::Valve(direction) => write!(f, "{}", direction.arrow()),
         }
     }
 }
@@ -503,7 +927,7 @@ mod test {
             .....
         "};
 
-        let map_actual = Map::parse_input(input);
+        let map_actual = Map::parse_input(input).unwrap();
         assert_eq!(map_actual.width, 5);
         assert_eq!(map_actual.height, 6);
 
@@ -539,7 +963,7 @@ mod test {
             .....
         "};
 
-        let map = Map::parse_input(input);
+        let map = Map::parse_input(input).unwrap();
 
         let coord = map.start;
 
@@ -569,16 +993,77 @@ mod test {
             .....
         "};
 
-        let map = Map::parse_input(input);
+        let map = Map::parse_input(input).unwrap();
         let start_coord = map.start;
 
-        let (direction, new_coord, new_pipe) = map.get_first_step();
+        let (direction, new_coord, new_pipe) = map.get_first_step().unwrap();
 
         assert_eq!(direction, Direction::East);
         assert_eq!(new_coord, start_coord.east().unwrap());
         assert_eq!(new_pipe, Pipe::Horizontal);
     }
 
+    #[test]
+    fn test_resolve_start() {
+        // The pipe north of the start points at it, but leads off the map
+        let input = indoc! {"
+            .|...
+            .S-7.
+            .|.|.
+            .L-J.
+        "};
+
+        let map = Map::parse_input(input).unwrap();
+        assert_eq!(map.resolve_start(), Ok((Direction::East, Direction::South)));
+
+        let (steps, filtered_map) = walk_map(input).unwrap();
+        assert_eq!(steps, 4);
+        assert_eq!(filtered_map.get(&map.start), Pipe::BendSE);
+        assert_eq!(filtered_map.get(&map.start.north().unwrap()), Pipe::Ground);
+    }
+
+    #[test]
+    fn test_map_errors() {
+        let dead_end = indoc! {"
+            .S-7.
+            .|.|.
+            .L-..
+        "};
+        assert_eq!(
+            walk_map(dead_end).unwrap_err(),
+            MapError::DeadEnd { x: 3, y: 2 }
+        );
+
+        assert_eq!(walk_map("S-.").unwrap_err(), MapError::UnclosedLoop);
+        assert_eq!(walk_map("..\n..").unwrap_err(), MapError::NoStart);
+        assert_eq!(walk_map("S.\n.S").unwrap_err(), MapError::MultipleStarts);
+
+        let invalid = walk_map("S-7\n|x|").unwrap_err();
+        let expected = MapError::InvalidCharacter {
+            character: 'x',
+            x: 1,
+            y: 1,
+        };
+        assert_eq!(invalid, expected);
+
+        let ragged = walk_map("S-7\n||").unwrap_err();
+        let expected = MapError::RaggedRow {
+            y: 1,
+            expected: 3,
+            found: 2,
+        };
+        assert_eq!(ragged, expected);
+
+        // Blank lines do not count towards the row
+        let invalid = walk_map("\nS-7\n\n|x|").unwrap_err();
+        let expected = MapError::InvalidCharacter {
+            character: 'x',
+            x: 1,
+            y: 1,
+        };
+        assert_eq!(invalid, expected);
+    }
+
     #[test]
     fn test_walk_map() {
         let input = indoc! {"
@@ -590,7 +1075,7 @@ mod test {
             .....
         "};
 
-        let steps = walk_map(input).0;
+        let steps = walk_map(input).unwrap().0;
         assert_eq!(steps, 4);
     }
 
@@ -603,7 +1088,7 @@ mod test {
             |F--J
             LJ...
         "};
-        let (steps, filtered_map) = walk_map(input);
+        let (steps, filtered_map) = walk_map(input).unwrap();
         assert_eq!(steps, 8);
 
         let filtered_map_string = filtered_map.to_string();
@@ -628,7 +1113,7 @@ mod test {
             |F--J
             LJ...
         "};
-        let (_, filtered_map) = walk_map(input);
+        let (_, filtered_map) = walk_map(input).unwrap();
 
         let filtered_map_string = filtered_map.to_string();
         println!("{}", filtered_map_string);
@@ -649,6 +1134,7 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::to_string_in_format_args)]
     fn test_is_inside_nightmare() {
         let input = indoc! {"
             FF7FSF7F7F7F7F7F---7
@@ -662,7 +1148,7 @@ mod test {
             L.L7LFJ|||||FJL7||LJ
             L7JLJL-JLJLJL--JLJ.L
         "};
-        let (_, filtered_map) = walk_map(input);
+        let (_, filtered_map) = walk_map(input).unwrap();
 
         println!("{}", filtered_map.to_string());
 
@@ -685,7 +1171,7 @@ mod test {
             ..........
         "};
 
-        let (_, mut filtered_map) = walk_map(input);
+        let (_, mut filtered_map) = walk_map(input).unwrap();
         filtered_map.pipes[filtered_map.start.linear_index()] = Pipe::BendSE;
 
         let inside_count = map_inside(&filtered_map);
@@ -1,7 +1,19 @@
 use ndarray as nd;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [command, rest @ ..] = args.as_slice() {
+        if command == "smudges" {
+            match rest.first().map_or(Ok(1), |k| k.parse()) {
+                Ok(differences) => report_smudges(differences),
+                Err(e) => eprintln!("Invalid number of smudges: {}", e),
+            }
+            return;
+        }
+    }
+
     problem_one();
+    problem_two();
 }
 
 fn problem_one() {
@@ -11,6 +23,28 @@ fn problem_one() {
     println!("Problem 1: {axis_totals}")
 }
 
+fn problem_two() {
+    let problem_text = include_str!("problem_text");
+    let axis_totals = get_smudged_totals(problem_text, 1);
+
+    println!("Problem 2: {axis_totals}")
+}
+
+/// Print where each pattern reflects when exactly `differences` cells are smudged
+fn report_smudges(differences: usize) {
+    let arrays = parse_array_list(include_str!("problem_text"));
+    for (n, arr) in arrays.iter().enumerate() {
+        match find_reflection(arr, differences) {
+            Some(reflection) => println!("Pattern {}: {}", n + 1, reflection),
+            None => println!("Pattern {}: no reflection", n + 1),
+        }
+    }
+    println!(
+        "Total: {}",
+        get_smudged_totals(include_str!("problem_text"), differences)
+    );
+}
+
 fn get_axis_totals(input: &str) -> usize {
     let arrays = parse_array_list(input);
 
@@ -20,6 +54,16 @@ fn get_axis_totals(input: &str) -> usize {
         .fold(0, |acc, x| acc + x.value())
 }
 
+/// Sum the axis values of reflections that hold apart from exactly `differences` cells
+fn get_smudged_totals(input: &str, differences: usize) -> usize {
+    let arrays = parse_array_list(input);
+
+    arrays
+        .iter()
+        .filter_map(|arr| find_reflection(arr, differences))
+        .fold(0, |acc, x| acc + x.axis.value())
+}
+
 #[derive(Debug, PartialEq)]
 enum SymmetryAxis {
     Row(usize),
@@ -35,6 +79,31 @@ impl SymmetryAxis {
     }
 }
 
+/// A cell as `(row, col)`
+type Cell = (usize, usize);
+
+/// A line of reflection, along with the cells that do not match their mirror image
+#[derive(Debug, PartialEq)]
+struct Reflection {
+    axis: SymmetryAxis,
+    /// Each smudged cell paired with its mirror image, fixing either one of them removes
+    /// the difference
+    smudges: Vec<(Cell, Cell)>,
+}
+
+impl std::fmt::Display for Reflection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.axis {
+            SymmetryAxis::Row(row) => write!(f, "row {}", row)?,
+            SymmetryAxis::Col(col) => write!(f, "col {}", col)?,
+        }
+        for (cell, mirror) in self.smudges.iter() {
+            write!(f, ", smudge at {:?} or {:?}", cell, mirror)?;
+        }
+        Ok(())
+    }
+}
+
 struct IndexPair {
     left: usize,
     right: usize,
@@ -69,12 +138,7 @@ impl IndexPair {
     }
 }
 
-/// Search for a symmetry axis in the array block
-///
-/// We iterate through the col (and row) of an array, if the selected column matches the next column
-/// then it is a candidate to be a symettry axis. We then "expand" the column pairs and keep checking
-/// if the columns are identical. If a pair is not identical, then we give up on this candinate.
-/// Otherwise, if we hit the edge of the array then this is an axis of symmetry.
+/// Search for a symmetry axis in the array block, see `scan_axis`
 fn scan_array(arr: &nd::Array2<bool>) -> Option<SymmetryAxis> {
     let row_axis = scan_rows(arr);
     if let Some(row) = row_axis {
@@ -86,69 +150,80 @@ fn scan_array(arr: &nd::Array2<bool>) -> Option<SymmetryAxis> {
         return Some(SymmetryAxis::Col(col));
     }
 
-    return None;
+    None
 }
 
-fn scan_cols(arr: &nd::Array2<bool>) -> Option<usize> {
-    let n_cols = arr.ncols();
-
-    for col_index in 0..n_cols - 1 {
-        let mut col_pair = IndexPair::from_index(col_index, n_cols).expect("Invalid main col pair");
-        let col_left = arr.column(col_pair.left);
-        let col_right = arr.column(col_pair.right);
-
-        // If we don't match the next col this isn't a mirror point so continue
-        if col_left != col_right {
-            continue;
-        }
-
-        loop {
-            if let Some(cols) = col_pair.expand() {
-                col_pair = cols;
-                let col_left = arr.column(col_pair.left);
-                let col_right = arr.column(col_pair.right);
-
-                // Candidate is not a true symmetry axis
-                if col_right != col_left {
-                    break;
-                }
-            } else {
-                // Here we hit the edges of the array while matching all previous mirror pairs
-                // So this is the symmetry axis
-                return Some(col_index + 1);
-            }
-        }
+/// Search for a reflection, rows first, where exactly `differences` cells do not match
+fn find_reflection(arr: &nd::Array2<bool>, differences: usize) -> Option<Reflection> {
+    if let Some((row, smudges)) = scan_axis(arr, nd::Axis(0), differences) {
+        return Some(Reflection {
+            axis: SymmetryAxis::Row(row),
+            smudges,
+        });
     }
 
-    None
+    scan_axis(arr, nd::Axis(1), differences).map(|(col, smudges)| Reflection {
+        axis: SymmetryAxis::Col(col),
+        smudges,
+    })
 }
 
-fn scan_rows(arr: &nd::Array2<bool>) -> Option<usize> {
-    // TODO: Merge this with the scan cols function
-    let n_rows = arr.nrows();
+fn scan_cols(arr: &nd::Array2<bool>) -> Option<usize> {
+    scan_axis(arr, nd::Axis(1), 0).map(|(col, _)| col)
+}
 
-    for row_index in 0..n_rows - 1 {
-        let mut row_pair = IndexPair::from_index(row_index, n_rows).expect("Invalid main row pair");
-        let row_left = arr.row(row_pair.left);
-        let row_right = arr.row(row_pair.right);
+fn scan_rows(arr: &nd::Array2<bool>) -> Option<usize> {
+    scan_axis(arr, nd::Axis(0), 0).map(|(row, _)| row)
+}
 
-        // If we don't match the next row this isn't a mirror point so continue
-        if row_left != row_right {
-            continue;
-        }
+/// Search for a line of reflection between the rows (`Axis(0)`) or columns (`Axis(1)`)
+/// of the array, where exactly `differences` cells do not match their mirror image.
+///
+/// We iterate through the rows (or cols) of an array, each one is a candidate to be next
+/// to a symmetry axis. We then "expand" the pairs, counting the cells that differ between
+/// them. If there are too many differences, then we give up on this candidate. Otherwise,
+/// if we hit the edge of the array with exactly the right number of differences then this
+/// is an axis of symmetry.
+fn scan_axis(
+    arr: &nd::Array2<bool>,
+    axis: nd::Axis,
+    differences: usize,
+) -> Option<(usize, Vec<(Cell, Cell)>)> {
+    let n_lanes = arr.len_of(axis);
+
+    for index in 0..n_lanes.saturating_sub(1) {
+        let mut pair = IndexPair::from_index(index, n_lanes).expect("Invalid main pair");
+        let mut smudges = Vec::new();
 
         loop {
-            if let Some(rows) = row_pair.expand() {
-                row_pair = rows;
-                let row_left = arr.row(row_pair.left);
-                let row_right = arr.row(row_pair.right);
+            let left = arr.index_axis(axis, pair.left);
+            let right = arr.index_axis(axis, pair.right);
+            for (position, _) in left
+                .iter()
+                .zip(right.iter())
+                .enumerate()
+                .filter(|(_, (l, r))| l != r)
+            {
+                smudges.push(match axis {
+                    nd::Axis(0) => ((pair.left, position), (pair.right, position)),
+                    _ => ((position, pair.left), (position, pair.right)),
+                });
+            }
 
-                if row_right != row_left {
-                    break;
-                }
+            // Candidate is not a true symmetry axis
+            if smudges.len() > differences {
+                break;
+            }
+
+            if let Some(next) = pair.expand() {
+                pair = next;
             } else {
-                // Here we hit the edges of the array while matching all previous mirror pairs
-                return Some(row_index + 1);
+                // Here we hit the edges of the array, so this is the symmetry axis if
+                // every smudge has been found
+                if smudges.len() == differences {
+                    return Some((index + 1, smudges));
+                }
+                break;
             }
         }
     }
@@ -290,4 +365,65 @@ mod test {
 
         assert_eq!(sample_one, 405)
     }
+
+    #[test]
+    fn test_sample_two() {
+        let test_data = indoc! {"
+            #.##..##.
+            ..#.##.#.
+            ##......#
+            ##......#
+            ..#.##.#.
+            ..##..##.
+            #.#.##.#.
+
+            #...##..#
+            #....#..#
+            ..##..###
+            #####.##.
+            #####.##.
+            ..##..###
+            #....#..#
+        "};
+        assert_eq!(get_smudged_totals(test_data, 1), 400);
+
+        let arrays = parse_array_list(test_data);
+        let expected = Reflection {
+            axis: SymmetryAxis::Row(3),
+            smudges: vec![((0, 0), (5, 0))],
+        };
+        assert_eq!(find_reflection(&arrays[0], 1), Some(expected));
+        let expected = Reflection {
+            axis: SymmetryAxis::Row(1),
+            smudges: vec![((0, 4), (1, 4))],
+        };
+        assert_eq!(find_reflection(&arrays[1], 1), Some(expected));
+    }
+
+    #[test]
+    fn test_k_smudges() {
+        let test_data = indoc! {"
+            #...##..#
+            #....#..#
+            ..##..###
+            #####.##.
+            #####.##.
+            ..##..###
+            #....#..#
+        "};
+        let mut arr = parse_array(test_data);
+        arr[(5, 1)] = !arr[(5, 1)];
+        arr[(6, 7)] = !arr[(6, 7)];
+
+        let reflection = find_reflection(&arr, 2).unwrap();
+        assert_eq!(reflection.axis, SymmetryAxis::Row(4));
+        assert_eq!(reflection.smudges, vec![((2, 1), (5, 1)), ((1, 7), (6, 7))]);
+        assert_eq!(
+            reflection.to_string(),
+            "row 4, smudge at (2, 1) or (5, 1), smudge at (1, 7) or (6, 7)"
+        );
+
+        // The smudges break the perfect reflection
+        assert_eq!(scan_array(&arr), None);
+    }
 }
@@ -0,0 +1,202 @@
+use crate::{parse_file, Counts, Game, Round};
+
+/// Print a summary of the bags that are consistent with the games in the problem text.
+///
+/// The search for the most likely bag covers every bag between the minimal bag and
+/// `span` extra cubes of each colour.
+pub(crate) fn bag_report(span: u32) {
+    let input = include_str!("./problem_text");
+    let (_, games) = parse_file(input).unwrap();
+
+    let minimal = minimal_bag(&games);
+    println!("Minimal bag: {}", minimal);
+
+    let puzzle_bag = Counts {
+        blue: 14,
+        red: 12,
+        green: 13,
+    };
+    let consistent = consistent_games(&games, &puzzle_bag);
+    println!(
+        "{} of {} games are consistent with {}",
+        consistent.len(),
+        games.len(),
+        puzzle_bag
+    );
+
+    match most_likely_bag(&games, span) {
+        Some((bag, log_likelihood)) => println!(
+            "Most likely bag: {} (log likelihood {:.3})",
+            bag, log_likelihood
+        ),
+        None => println!("No bag within the search range can produce these games"),
+    }
+}
+
+/// Returns the smallest bag that could have produced every round of every game
+pub(crate) fn minimal_bag(games: &[Game]) -> Counts {
+    games
+        .iter()
+        .flat_map(|game| game.rounds.iter())
+        .fold(Counts::new(), |acc, round| acc.update(round))
+}
+
+/// Returns the ids of the games that could have been played with the given bag
+pub(crate) fn consistent_games(games: &[Game], bag: &Counts) -> Vec<u32> {
+    games
+        .iter()
+        .filter(|game| game.is_game_valid(bag.red, bag.green, bag.blue))
+        .map(|game| game.id)
+        .collect()
+}
+
+/// Natural log of the probability of drawing exactly this round from the bag without
+/// replacement, this is `-inf` for an impossible round.
+///
+/// This is the multivariate hypergeometric distribution, the cubes are assumed to be
+/// returned to the bag between rounds.
+pub(crate) fn round_log_probability(round: &Round, bag: &Counts) -> f64 {
+    if !round.is_round_valid(bag.red, bag.green, bag.blue) {
+        return f64::NEG_INFINITY;
+    }
+
+    let drawn = round.blue + round.red + round.green;
+    let total = bag.blue + bag.red + bag.green;
+
+    ln_choose(bag.blue, round.blue)
+        + ln_choose(bag.red, round.red)
+        + ln_choose(bag.green, round.green)
+        - ln_choose(total, drawn)
+}
+
+/// Log likelihood of observing every round of the game from the given bag
+pub(crate) fn game_log_likelihood(game: &Game, bag: &Counts) -> f64 {
+    game.rounds
+        .iter()
+        .map(|round| round_log_probability(round, bag))
+        .sum()
+}
+
+/// Searches the bags between the minimal bag and `span` extra cubes of each colour,
+/// returning the one that maximises the likelihood of every game along with its log
+/// likelihood.
+///
+/// The likelihood does not have to peak at a finite bag, so the search must be bounded.
+pub(crate) fn most_likely_bag(games: &[Game], span: u32) -> Option<(Counts, f64)> {
+    let minimal = minimal_bag(games);
+    let mut best: Option<(Counts, f64)> = None;
+
+    for blue in minimal.blue..=minimal.blue + span {
+        for red in minimal.red..=minimal.red + span {
+            for green in minimal.green..=minimal.green + span {
+                let bag = Counts { blue, red, green };
+                let log_likelihood: f64 = games
+                    .iter()
+                    .map(|game| game_log_likelihood(game, &bag))
+                    .sum();
+
+                if !log_likelihood.is_finite() {
+                    continue;
+                }
+                if best.is_none_or(|(_, best_ll)| log_likelihood > best_ll) {
+                    best = Some((bag, log_likelihood));
+                }
+            }
+        }
+    }
+
+    best
+}
+
+/// Natural log of the binomial coefficient `n choose k`, `-inf` if `k > n`
+fn ln_choose(n: u32, k: u32) -> f64 {
+    if k > n {
+        return f64::NEG_INFINITY;
+    }
+    let k = k.min(n - k);
+    (1..=k)
+        .map(|i| ((n - k + i) as f64).ln() - (i as f64).ln())
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    fn sample_games() -> Vec<Game> {
+        let input = indoc! {"
+            Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+            Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+            Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+            Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+            Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+        "};
+        parse_file(input).unwrap().1
+    }
+
+    #[test]
+    fn test_minimal_bag() {
+        let expected = Counts {
+            blue: 15,
+            red: 20,
+            green: 13,
+        };
+        assert_eq!(minimal_bag(&sample_games()), expected);
+    }
+
+    #[test]
+    fn test_consistent_games() {
+        let bag = Counts {
+            blue: 14,
+            red: 12,
+            green: 13,
+        };
+        assert_eq!(consistent_games(&sample_games(), &bag), vec![1, 2, 5]);
+
+        // Every game is consistent with the minimal bag
+        let games = sample_games();
+        let minimal = minimal_bag(&games);
+        assert_eq!(consistent_games(&games, &minimal), vec![1, 2, 3, 4, 5]);
+    }
+
+    fn round_probability(round: &Round, bag: &Counts) -> f64 {
+        round_log_probability(round, bag).exp()
+    }
+
+    #[test]
+    fn test_round_probability() {
+        let bag = Counts {
+            blue: 2,
+            red: 2,
+            green: 0,
+        };
+        // One of each from two blue and two red: (2 * 2) / 6
+        let round = Round::from_tuple(1, 1, 0);
+        assert!((round_probability(&round, &bag) - 4.0 / 6.0).abs() < 1e-12);
+
+        // Drawing the whole bag is certain
+        let round = Round::from_tuple(2, 2, 0);
+        assert!((round_probability(&round, &bag) - 1.0).abs() < 1e-12);
+
+        // There is no green in the bag
+        let round = Round::from_tuple(0, 0, 1);
+        assert_eq!(round_probability(&round, &bag), 0.0);
+    }
+
+    #[test]
+    fn test_most_likely_bag() {
+        let games = sample_games();
+        let (bag, log_likelihood) = most_likely_bag(&games, 3).unwrap();
+        assert!(log_likelihood.is_finite());
+
+        let minimal = minimal_bag(&games);
+        assert!(bag.blue >= minimal.blue && bag.red >= minimal.red && bag.green >= minimal.green);
+
+        let minimal_ll: f64 = games
+            .iter()
+            .map(|game| game_log_likelihood(game, &minimal))
+            .sum();
+        assert!(log_likelihood >= minimal_ll);
+    }
+}
@@ -1,15 +1,45 @@
+mod analysis;
+mod query;
+mod serialise;
+
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::character::complete::{space0, space1, line_ending};
+use nom::character::complete::{line_ending, space0, space1};
 use nom::combinator::map;
 use nom::multi::separated_list1;
 use nom::sequence::{delimited, pair, separated_pair};
 use nom::IResult;
 use nom_supreme::parser_ext::ParserExt;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
 fn main() {
-    problem_one();
-    problem_two();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        None => {
+            problem_one();
+            problem_two();
+        }
+        Some("bag") => {
+            let span = args
+                .get(1)
+                .map_or(5, |s| s.parse().expect("Invalid search span"));
+            analysis::bag_report(span);
+        }
+        Some("query") => match args.get(1) {
+            Some(query) => query::query_report(query),
+            None => eprintln!("Expected a query, e.g. \"max(red) > 10 && any_round(blue == 0)\""),
+        },
+        Some("export") => {
+            let format = args.get(1).map_or("text", String::as_str);
+            serialise::export_report(format, args.get(2).map(String::as_str));
+        }
+        Some(command) => eprintln!(
+            "Unknown command {}, expected one of: bag, query, export",
+            command
+        ),
+    }
 }
 
 fn problem_one() {
@@ -47,12 +77,24 @@ fn get_power_total(input: &str) -> u32 {
         .sum()
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 struct Game {
     id: u32,
     rounds: Vec<Round>,
 }
 
+impl Display for Game {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Game {}: ", self.id)?;
+        for (i, round) in self.rounds.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", round)?;
+        }
+        Ok(())
+    }
+}
 
 impl Game {
     /// Return true if all rounds are valid
@@ -70,7 +112,7 @@ impl Game {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 struct Round {
     blue: u32,
     red: u32,
@@ -81,9 +123,38 @@ impl Round {
     fn is_round_valid(&self, max_red: u32, max_green: u32, max_blue: u32) -> bool {
         self.red <= max_red && self.green <= max_green && self.blue <= max_blue
     }
+
+    /// Returns the colours that were drawn in this round, in the canonical order
+    fn get_drawn(&self) -> impl Iterator<Item = (Colour, u32)> {
+        [
+            (Colour::Blue, self.blue),
+            (Colour::Red, self.red),
+            (Colour::Green, self.green),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+    }
+}
+
+impl Display for Round {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Zero counts are omitted, but an empty round still needs to be parsable
+        let mut drawn = self.get_drawn().peekable();
+        if drawn.peek().is_none() {
+            return write!(f, "0 {}", Colour::Blue);
+        }
+
+        for (i, (colour, count)) in drawn.enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} {}", count, colour)?;
+        }
+        Ok(())
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 struct Counts {
     blue: u32,
     red: u32,
@@ -123,13 +194,35 @@ impl Counts {
         self.blue * self.red * self.green
     }
 }
-#[derive(Debug, PartialEq, Clone, Copy)]
+
+impl Display for Counts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} red, {} green, {} blue",
+            self.red, self.green, self.blue
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum Colour {
     Blue,
     Red,
     Green,
 }
 
+impl Display for Colour {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Colour::Blue => write!(f, "blue"),
+            Colour::Red => write!(f, "red"),
+            Colour::Green => write!(f, "green"),
+        }
+    }
+}
+
 fn parse_file(input: &str) -> IResult<&str, Vec<Game>> {
     separated_list1(line_ending, parse_game)(input)
 }
@@ -176,7 +269,7 @@ mod tests {
     use super::*;
 
     impl Round {
-        fn from_tuple(blue: u32, red: u32, green: u32) -> Self {
+        pub(crate) fn from_tuple(blue: u32, red: u32, green: u32) -> Self {
             Round { blue, red, green }
         }
     }
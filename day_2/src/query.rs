@@ -0,0 +1,439 @@
+use crate::{parse_file, Colour, Game, Round};
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::{multispace0, satisfy};
+use nom::combinator::{all_consuming, not};
+use nom::multi::many0;
+use nom::sequence::{delimited, pair, preceded, terminated};
+use nom::IResult;
+use nom::Parser;
+use nom_supreme::parser_ext::ParserExt;
+use std::fmt::Display;
+
+/// Print the games in the problem text that match the query, along with the sum of
+/// their ids and powers.
+pub(crate) fn query_report(query: &str) {
+    let query = match Query::parse(query) {
+        Ok(query) => query,
+        Err(e) => {
+            eprintln!("Invalid query: {}", e);
+            return;
+        }
+    };
+
+    let input = include_str!("./problem_text");
+    let (_, games) = parse_file(input).unwrap();
+    let summary = query.summarise(&games);
+
+    let ids = summary
+        .ids
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    println!("Matching games: {}", ids);
+    println!("Number of games: {}", summary.ids.len());
+    println!("Sum of ids: {}", summary.id_sum);
+    println!("Sum of powers: {}", summary.power_sum);
+}
+
+/// A predicate over a game, for example `max(red) > 10 && any_round(blue == 0)`.
+///
+/// Outside of a round the values are `max(colour)`, `min(colour)`, `sum(colour)`,
+/// `rounds`, `id` and `power`. Inside `any_round(..)` or `all_round(..)` a bare colour
+/// refers to the count of that colour in the current round.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Query {
+    Compare(Value, Comparison, Value),
+    Not(Box<Query>),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    AnyRound(Box<Query>),
+    AllRound(Box<Query>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum Value {
+    Number(u32),
+    /// Count of the colour in the current round
+    Count(Colour),
+    Max(Colour),
+    Min(Colour),
+    Sum(Colour),
+    Rounds,
+    Id,
+    Power,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum QueryError {
+    /// The query could not be parsed, contains the remaining input
+    Syntax(String),
+    /// A value was used outside of the scope where it has a meaning
+    Scope(String),
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::Syntax(rem) => write!(f, "unable to parse query at '{}'", rem),
+            QueryError::Scope(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// The games that matched a query along with some aggregate values
+#[derive(Debug, PartialEq)]
+pub(crate) struct QuerySummary {
+    pub(crate) ids: Vec<u32>,
+    pub(crate) id_sum: u32,
+    pub(crate) power_sum: u32,
+}
+
+impl Query {
+    pub(crate) fn parse(input: &str) -> Result<Query, QueryError> {
+        let (_, query) =
+            all_consuming(parse_or.terminated(multispace0))(input).map_err(|e| match e {
+                nom::Err::Error(e) | nom::Err::Failure(e) => {
+                    QueryError::Syntax(e.input.to_string())
+                }
+                nom::Err::Incomplete(_) => QueryError::Syntax(String::new()),
+            })?;
+        query.check_scope(false)?;
+        Ok(query)
+    }
+
+    pub(crate) fn matches(&self, game: &Game) -> bool {
+        self.evaluate(game, None)
+    }
+
+    pub(crate) fn summarise(&self, games: &[Game]) -> QuerySummary {
+        let matching = games.iter().filter(|game| self.matches(game));
+
+        let mut ids = Vec::new();
+        let mut power_sum = 0;
+        for game in matching {
+            ids.push(game.id);
+            power_sum += game.get_max_counts().get_power();
+        }
+
+        QuerySummary {
+            id_sum: ids.iter().sum(),
+            ids,
+            power_sum,
+        }
+    }
+
+    /// Evaluate the query, `round` is only set inside of a round quantifier
+    fn evaluate(&self, game: &Game, round: Option<&Round>) -> bool {
+        match self {
+            Query::Compare(lhs, cmp, rhs) => {
+                cmp.compare(lhs.resolve(game, round), rhs.resolve(game, round))
+            }
+            Query::Not(inner) => !inner.evaluate(game, round),
+            Query::And(lhs, rhs) => lhs.evaluate(game, round) && rhs.evaluate(game, round),
+            Query::Or(lhs, rhs) => lhs.evaluate(game, round) || rhs.evaluate(game, round),
+            Query::AnyRound(inner) => game.rounds.iter().any(|r| inner.evaluate(game, Some(r))),
+            Query::AllRound(inner) => game.rounds.iter().all(|r| inner.evaluate(game, Some(r))),
+        }
+    }
+
+    /// Ensure that round counts only appear inside a round quantifier and that
+    /// quantifiers are not nested.
+    fn check_scope(&self, in_round: bool) -> Result<(), QueryError> {
+        match self {
+            Query::Compare(lhs, _, rhs) => {
+                lhs.check_scope(in_round)?;
+                rhs.check_scope(in_round)
+            }
+            Query::Not(inner) => inner.check_scope(in_round),
+            Query::And(lhs, rhs) | Query::Or(lhs, rhs) => {
+                lhs.check_scope(in_round)?;
+                rhs.check_scope(in_round)
+            }
+            Query::AnyRound(inner) | Query::AllRound(inner) => {
+                if in_round {
+                    return Err(QueryError::Scope(
+                        "round quantifiers cannot be nested".to_string(),
+                    ));
+                }
+                inner.check_scope(true)
+            }
+        }
+    }
+}
+
+impl Value {
+    /// Resolve the value, the scope must have been checked beforehand
+    fn resolve(&self, game: &Game, round: Option<&Round>) -> u32 {
+        let counts = |colour: Colour| game.rounds.iter().map(move |r| colour_count(r, colour));
+        match self {
+            Value::Number(n) => *n,
+            Value::Count(colour) => colour_count(round.expect("Count outside of a round"), *colour),
+            Value::Max(colour) => counts(*colour).max().unwrap_or(0),
+            Value::Min(colour) => counts(*colour).min().unwrap_or(0),
+            Value::Sum(colour) => counts(*colour).sum(),
+            Value::Rounds => game.rounds.len() as u32,
+            Value::Id => game.id,
+            Value::Power => game.get_max_counts().get_power(),
+        }
+    }
+
+    fn check_scope(&self, in_round: bool) -> Result<(), QueryError> {
+        match self {
+            Value::Count(colour) if !in_round => Err(QueryError::Scope(format!(
+                "{:?} is only defined inside any_round or all_round",
+                colour
+            ))),
+            Value::Number(_) | Value::Count(_) => Ok(()),
+            _ if in_round => Err(QueryError::Scope(format!(
+                "{:?} is not defined inside a round",
+                self
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Comparison {
+    fn compare(&self, lhs: u32, rhs: u32) -> bool {
+        match self {
+            Comparison::Eq => lhs == rhs,
+            Comparison::Ne => lhs != rhs,
+            Comparison::Lt => lhs < rhs,
+            Comparison::Le => lhs <= rhs,
+            Comparison::Gt => lhs > rhs,
+            Comparison::Ge => lhs >= rhs,
+        }
+    }
+}
+
+fn colour_count(round: &Round, colour: Colour) -> u32 {
+    match colour {
+        Colour::Blue => round.blue,
+        Colour::Red => round.red,
+        Colour::Green => round.green,
+    }
+}
+
+/// Wrap the parser so that it ignores leading whitespace
+fn ws<'a, O>(
+    inner: impl Parser<&'a str, O, nom::error::Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O> {
+    preceded(multispace0, inner)
+}
+
+/// Match `name` only as a whole word, so that `id` does not match the start of `ids`
+fn keyword<'a>(name: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    terminated(
+        tag(name),
+        not(satisfy(|c: char| c.is_alphanumeric() || c == '_')),
+    )
+}
+
+fn parse_or(input: &str) -> IResult<&str, Query> {
+    let (rem, first) = parse_and(input)?;
+    let (rem, rest) = many0(preceded(ws(tag("||")), parse_and))(rem)?;
+
+    let query = rest
+        .into_iter()
+        .fold(first, |acc, q| Query::Or(Box::new(acc), Box::new(q)));
+    Ok((rem, query))
+}
+
+fn parse_and(input: &str) -> IResult<&str, Query> {
+    let (rem, first) = parse_unary(input)?;
+    let (rem, rest) = many0(preceded(ws(tag("&&")), parse_unary))(rem)?;
+
+    let query = rest
+        .into_iter()
+        .fold(first, |acc, q| Query::And(Box::new(acc), Box::new(q)));
+    Ok((rem, query))
+}
+
+fn parse_unary(input: &str) -> IResult<&str, Query> {
+    let not = preceded(ws(tag("!")), parse_unary).map(|q| Query::Not(Box::new(q)));
+    let brackets = delimited(ws(tag("(")), parse_or, ws(tag(")")));
+    let any_round = delimited(
+        pair(ws(keyword("any_round")), ws(tag("("))),
+        parse_or,
+        ws(tag(")")),
+    )
+    .map(|q| Query::AnyRound(Box::new(q)));
+    let all_round = delimited(
+        pair(ws(keyword("all_round")), ws(tag("("))),
+        parse_or,
+        ws(tag(")")),
+    )
+    .map(|q| Query::AllRound(Box::new(q)));
+
+    alt((not, brackets, any_round, all_round, parse_comparison))(input)
+}
+
+fn parse_comparison(input: &str) -> IResult<&str, Query> {
+    let comparison = alt((
+        tag("==").value(Comparison::Eq),
+        tag("!=").value(Comparison::Ne),
+        tag("<=").value(Comparison::Le),
+        tag(">=").value(Comparison::Ge),
+        tag("<").value(Comparison::Lt),
+        tag(">").value(Comparison::Gt),
+    ));
+
+    pair(parse_value, pair(ws(comparison), parse_value))
+        .map(|(lhs, (cmp, rhs))| Query::Compare(lhs, cmp, rhs))
+        .parse(input)
+}
+
+fn parse_value(input: &str) -> IResult<&str, Value> {
+    let aggregate = |name: &'static str| {
+        delimited(
+            pair(keyword(name), ws(tag("("))),
+            ws(parse_colour),
+            ws(tag(")")),
+        )
+    };
+
+    ws(alt((
+        nom::character::complete::u32.map(Value::Number),
+        aggregate("max").map(Value::Max),
+        aggregate("min").map(Value::Min),
+        aggregate("sum").map(Value::Sum),
+        keyword("rounds").value(Value::Rounds),
+        keyword("id").value(Value::Id),
+        keyword("power").value(Value::Power),
+        parse_colour.map(Value::Count),
+    )))(input)
+}
+
+fn parse_colour(input: &str) -> IResult<&str, Colour> {
+    alt((
+        keyword("blue").value(Colour::Blue),
+        keyword("red").value(Colour::Red),
+        keyword("green").value(Colour::Green),
+    ))(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    fn sample_games() -> Vec<Game> {
+        let input = indoc! {"
+            Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+            Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+            Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+            Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+            Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+        "};
+        parse_file(input).unwrap().1
+    }
+
+    fn matching_ids(query: &str) -> Vec<u32> {
+        let query = Query::parse(query).unwrap();
+        query.summarise(&sample_games()).ids
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let expected = Query::And(
+            Box::new(Query::Compare(
+                Value::Max(Colour::Red),
+                Comparison::Gt,
+                Value::Number(10),
+            )),
+            Box::new(Query::AnyRound(Box::new(Query::Compare(
+                Value::Count(Colour::Blue),
+                Comparison::Eq,
+                Value::Number(0),
+            )))),
+        );
+        let actual = Query::parse("max(red) > 10 && any_round(blue == 0)").unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_query_matches() {
+        assert_eq!(
+            matching_ids("max(red) > 10 && any_round(blue == 0)"),
+            vec![3, 4]
+        );
+        assert_eq!(matching_ids("rounds >= 3"), vec![1, 2, 3, 4]);
+        assert_eq!(matching_ids("all_round(green > 0)"), vec![2, 3, 4, 5]);
+        assert_eq!(
+            matching_ids("!(id == 1 || id == 2) && sum(blue) < 10"),
+            vec![5]
+        );
+    }
+
+    #[test]
+    fn test_puzzle_bag_query() {
+        let query = Query::parse("max(red) <= 12 && max(green) <= 13 && max(blue) <= 14").unwrap();
+        let summary = query.summarise(&sample_games());
+        assert_eq!(summary.ids, vec![1, 2, 5]);
+        assert_eq!(summary.id_sum, 8);
+        assert_eq!(summary.power_sum, 48 + 12 + 36);
+
+        let query = Query::parse("rounds > 0").unwrap();
+        assert_eq!(query.summarise(&sample_games()).power_sum, 2286);
+    }
+
+    #[test]
+    fn test_query_errors() {
+        assert!(matches!(
+            Query::parse("max(red) >"),
+            Err(QueryError::Syntax(_))
+        ));
+        assert!(matches!(
+            Query::parse("rounds > 1 &&"),
+            Err(QueryError::Syntax(_))
+        ));
+        assert!(matches!(Query::parse("red > 1"), Err(QueryError::Scope(_))));
+        assert!(matches!(
+            Query::parse("any_round(max(red) > 1)"),
+            Err(QueryError::Scope(_))
+        ));
+        assert!(matches!(
+            Query::parse("any_round(all_round(red > 1))"),
+            Err(QueryError::Scope(_))
+        ));
+    }
+
+    #[test]
+    fn test_keyword_boundaries() {
+        // Keywords must not be the start of a longer word
+        assert!(matches!(
+            Query::parse("ids > 1"),
+            Err(QueryError::Syntax(_))
+        ));
+        assert!(matches!(
+            Query::parse("any_round(reddish > 1)"),
+            Err(QueryError::Syntax(_))
+        ));
+        assert!(matches!(
+            Query::parse("maximum(red) > 1"),
+            Err(QueryError::Syntax(_))
+        ));
+
+        // Whitespace is allowed before the opening bracket
+        assert_eq!(
+            Query::parse("any_round (blue == 0) && max (red) > 10"),
+            Query::parse("any_round(blue == 0) && max(red) > 10")
+        );
+        assert_eq!(
+            matching_ids("all_round (green > 0) && id != 2"),
+            vec![3, 4, 5]
+        );
+    }
+}
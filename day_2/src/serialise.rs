@@ -0,0 +1,148 @@
+use crate::{parse_file, Colour, Game};
+use serde::Serialize;
+
+/// Write the games to stdout in the given format, one of `text`, `json` or `csv`.
+///
+/// The games are read from `path` if given, either as json or in the puzzle format,
+/// otherwise the problem text is used.
+pub(crate) fn export_report(format: &str, path: Option<&str>) {
+    let games = match path {
+        None => parse_file(include_str!("./problem_text")).unwrap().1,
+        Some(path) => {
+            let input = std::fs::read_to_string(path).expect("Failed to read input file");
+            if path.ends_with(".json") {
+                from_json(&input).expect("Failed to parse json")
+            } else {
+                parse_file(input.trim()).unwrap().1
+            }
+        }
+    };
+
+    let output = match format {
+        "text" => to_text(&games),
+        "json" => to_json(&games).expect("Failed to write json"),
+        "csv" => to_csv(&games).expect("Failed to write csv"),
+        _ => {
+            eprintln!(
+                "Unknown format {}, expected one of: text, json, csv",
+                format
+            );
+            return;
+        }
+    };
+    println!("{}", output);
+}
+
+/// Write the games in the canonical `Game N: 3 blue, 4 red; ...` format, one per line
+pub(crate) fn to_text(games: &[Game]) -> String {
+    games
+        .iter()
+        .map(|game| game.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub(crate) fn to_json(games: &[Game]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(games)
+}
+
+pub(crate) fn from_json(input: &str) -> serde_json::Result<Vec<Game>> {
+    serde_json::from_str(input)
+}
+
+/// A single colour count from a round, the rounds are numbered from one
+#[derive(Debug, PartialEq, Serialize)]
+struct CsvRecord {
+    game: u32,
+    round: usize,
+    colour: Colour,
+    count: u32,
+}
+
+/// Write the games as a flat table of `game,round,colour,count`.
+///
+/// As with the text format, colours that were not drawn in a round are omitted.
+pub(crate) fn to_csv(games: &[Game]) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+
+    for game in games {
+        for (round_idx, round) in game.rounds.iter().enumerate() {
+            for (colour, count) in round.get_drawn() {
+                writer.serialize(CsvRecord {
+                    game: game.id,
+                    round: round_idx + 1,
+                    colour,
+                    count,
+                })?;
+            }
+        }
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8(bytes).expect("csv output is always utf-8"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse_game, Round};
+    use indoc::indoc;
+    use proptest::prelude::*;
+
+    fn round_strategy() -> impl Strategy<Value = Round> {
+        (0..30u32, 0..30u32, 0..30u32).prop_map(|(blue, red, green)| Round { blue, red, green })
+    }
+
+    fn game_strategy() -> impl Strategy<Value = Game> {
+        (1..1000u32, prop::collection::vec(round_strategy(), 1..8))
+            .prop_map(|(id, rounds)| Game { id, rounds })
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_display_identity(game in game_strategy()) {
+            let text = game.to_string();
+            let (rem, parsed) = parse_game(&text).unwrap();
+            prop_assert_eq!(rem, "");
+            prop_assert_eq!(parsed, game);
+        }
+
+        #[test]
+        fn test_json_round_trip(games in prop::collection::vec(game_strategy(), 1..5)) {
+            let json = to_json(&games).unwrap();
+            prop_assert_eq!(from_json(&json).unwrap(), games);
+        }
+    }
+
+    #[test]
+    fn test_display_game() {
+        let line = "Game 1: 4 red, 3 blue; 1 red, 2 green, 6 blue; 2 green";
+        let (_, game) = parse_game(line).unwrap();
+        let expected = "Game 1: 3 blue, 4 red; 6 blue, 1 red, 2 green; 2 green";
+        assert_eq!(game.to_string(), expected);
+    }
+
+    #[test]
+    fn test_to_text() {
+        let input = indoc! {"
+            Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+            Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue"};
+        let expected = indoc! {"
+            Game 1: 3 blue, 4 red; 6 blue, 1 red, 2 green; 2 green
+            Game 2: 1 blue, 2 green; 4 blue, 1 red, 3 green; 1 blue, 1 green"};
+        let (_, games) = parse_file(input).unwrap();
+        assert_eq!(to_text(&games), expected);
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let (_, games) = parse_file("Game 7: 3 blue, 4 red; 2 green").unwrap();
+        let expected = indoc! {"
+            game,round,colour,count
+            7,1,blue,3
+            7,1,red,4
+            7,2,green,2
+        "};
+        assert_eq!(to_csv(&games).unwrap(), expected);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::Card;
+use std::collections::HashSet;
+use std::time::Instant;
+use xorshift::XorShift;
+
+/// Compare the bitset backed cards against the original `HashSet` and `Vec` layout on a
+/// generated table of cards.
+pub(crate) fn benchmark(n_cards: u32) {
+    let mut rng = XorShift::new(0x5eed);
+    let tables = (1..=n_cards)
+        .map(|id| generate_numbers(id, &mut rng))
+        .collect::<Vec<_>>();
+
+    let start = Instant::now();
+    let cards = tables
+        .iter()
+        .map(|(id, winning, collected)| Card::new(*id, winning, collected))
+        .collect::<Vec<_>>();
+    let bitset_build = start.elapsed();
+
+    let start = Instant::now();
+    let bitset_matches: u64 = cards.iter().map(|card| card.n_matches() as u64).sum();
+    let bitset_count = start.elapsed();
+
+    let start = Instant::now();
+    let hash_cards = tables
+        .iter()
+        .map(|(_, winning, collected)| HashCard::new(winning, collected))
+        .collect::<Vec<_>>();
+    let hash_build = start.elapsed();
+
+    let start = Instant::now();
+    let hash_matches: u64 = hash_cards.iter().map(|card| card.n_matches() as u64).sum();
+    let hash_count = start.elapsed();
+
+    assert_eq!(bitset_matches, hash_matches, "Representations disagree");
+
+    println!("Cards: {}, total matches: {}", n_cards, bitset_matches);
+    println!(
+        "Bitset:  build {:>10.2?}, count {:>10.2?}",
+        bitset_build, bitset_count
+    );
+    println!(
+        "HashSet: build {:>10.2?}, count {:>10.2?}",
+        hash_build, hash_count
+    );
+}
+
+/// The original layout of a card, kept to compare against
+struct HashCard {
+    winning_numbers: HashSet<u32>,
+    collected_numbers: Vec<u32>,
+}
+
+impl HashCard {
+    fn new(winning_numbers: &[u32], collected_numbers: &[u32]) -> Self {
+        HashCard {
+            winning_numbers: winning_numbers.iter().copied().collect(),
+            collected_numbers: collected_numbers.to_vec(),
+        }
+    }
+
+    fn n_matches(&self) -> u32 {
+        self.collected_numbers
+            .iter()
+            .filter(|n| self.winning_numbers.contains(n))
+            .count() as u32
+    }
+}
+
+/// Generate a card shaped like the puzzle input, 10 winning numbers and 25 collected
+/// numbers below 100, with the occasional larger number to exercise the fallback.
+fn generate_numbers(id: u32, rng: &mut XorShift) -> (u32, Vec<u32>, Vec<u32>) {
+    let mut number = || {
+        if rng.next_u64().is_multiple_of(50) {
+            100 + (rng.next_u64() % 1000) as u32
+        } else {
+            1 + (rng.next_u64() % 99) as u32
+        }
+    };
+
+    let winning = (0..10).map(|_| number()).collect();
+    let collected = (0..25).map(|_| number()).collect();
+    (id, winning, collected)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_representations_agree() {
+        let mut rng = XorShift::new(42);
+        for id in 1..=1000 {
+            let (id, winning, collected) = generate_numbers(id, &mut rng);
+            let card = Card::new(id, &winning, &collected);
+            let hash_card = HashCard::new(&winning, &collected);
+            assert_eq!(card.n_matches(), hash_card.n_matches());
+        }
+    }
+}
@@ -1,3 +1,7 @@
+mod benchmark;
+mod number_set;
+mod problem_two;
+
 use nom::bytes::complete::tag;
 use nom::character::complete::{space0, space1};
 use nom::multi::separated_list1;
@@ -5,11 +9,25 @@ use nom::sequence::{delimited, tuple};
 use nom::IResult;
 use nom::Parser;
 use nom_supreme::parser_ext::ParserExt;
-use std::collections::HashSet;
+use number_set::NumberSet;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("bench") {
+        let n_cards = args
+            .get(1)
+            .map_or(1_000_000, |s| s.parse().expect("Invalid number of cards"));
+        benchmark::benchmark(n_cards);
+        return;
+    }
+
+    report_duplicates(include_str!("problem_text"));
+
     let score = problem_one();
     println!("Problem One: {}", score);
+
+    let total_cards = problem_two::problem_two();
+    println!("Problem Two: {}", total_cards);
 }
 
 fn problem_one() -> u32 {
@@ -22,27 +40,82 @@ fn get_total_score(lines: &str) -> u32 {
     cards.iter().map(|card| card.score()).sum()
 }
 
+/// Print a warning for every number that is repeated on a card
+fn report_duplicates(lines: &str) {
+    for card in parse_lines(lines) {
+        for duplicate in card.duplicates.iter() {
+            match duplicate {
+                Duplicate::Winning(n) => {
+                    eprintln!("Card {}: winning number {} is repeated", card.id, n)
+                }
+                Duplicate::Collected(n) => {
+                    eprintln!("Card {}: collected number {} is repeated", card.id, n)
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 struct Card {
     id: u32,
-    winning_numbers: HashSet<u32>,
-    collected_numbers: Vec<u32>,
+    winning_numbers: NumberSet,
+    collected_numbers: NumberSet,
+    /// Every repeat of a number on the card, in the order they were read
+    duplicates: Vec<Duplicate>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Duplicate {
+    Winning(u32),
+    Collected(u32),
 }
 
 impl Card {
+    fn new(id: u32, winning_numbers: &[u32], collected_numbers: &[u32]) -> Card {
+        let mut duplicates = Vec::new();
+
+        let mut winning_set = NumberSet::new();
+        for &n in winning_numbers {
+            if !winning_set.insert(n) {
+                duplicates.push(Duplicate::Winning(n));
+            }
+        }
+
+        let mut collected_set = NumberSet::new();
+        for &n in collected_numbers {
+            if !collected_set.insert(n) {
+                duplicates.push(Duplicate::Collected(n));
+            }
+        }
+
+        Card {
+            id,
+            winning_numbers: winning_set,
+            collected_numbers: collected_set,
+            duplicates,
+        }
+    }
+
     fn from_line(line: &str) -> Option<Card> {
         parse_card(line).map(|(_, card)| card).ok()
     }
 
-    fn score(&self) -> u32 {
-        // This is a bit inefficient, if we knew that there are no repeats in the collected numbers
-        // then we could do everything with sets.
-        let n_matches = self
-            .collected_numbers
+    /// Returns the number of collected numbers that are also winning numbers
+    fn n_matches(&self) -> u32 {
+        // A repeated collected number scores again for each repeat
+        let repeats = self
+            .duplicates
             .iter()
-            .filter(|n| self.winning_numbers.contains(n))
+            .filter(|d| matches!(d, Duplicate::Collected(n) if self.winning_numbers.contains(*n)))
             .count() as u32;
 
+        self.winning_numbers.count_common(&self.collected_numbers) + repeats
+    }
+
+    fn score(&self) -> u32 {
+        let n_matches = self.n_matches();
+
         if n_matches == 0 {
             0
         } else {
@@ -51,6 +124,7 @@ impl Card {
     }
 }
 
+#[allow(clippy::redundant_closure)]
 fn parse_lines(lines: &str) -> Vec<Card> {
     lines
         .lines()
@@ -73,10 +147,8 @@ fn parse_card(line: &str) -> IResult<&str, Card> {
         separator_parser,
         collected_numbers_parser,
     ))
-    .map(|(id, winning_numbers, _, collected_numbers)| Card {
-        id,
-        winning_numbers: winning_numbers.into_iter().collect(),
-        collected_numbers,
+    .map(|(id, winning_numbers, _, collected_numbers)| {
+        Card::new(id, &winning_numbers, &collected_numbers)
     });
 
     parser.parse(line)
@@ -90,24 +162,17 @@ mod tests {
     #[test]
     fn test_parse_card() {
         let input = "Card 1: 1 2 3 4 5 | 1 2 3 4 5";
-        let expected = Card {
-            id: 1,
-            winning_numbers: [1, 2, 3, 4, 5].iter().cloned().collect(),
-            collected_numbers: [1, 2, 3, 4, 5].to_vec(),
-        };
+        let expected = Card::new(1, &[1, 2, 3, 4, 5], &[1, 2, 3, 4, 5]);
         let (rem, card) = parse_card(input).unwrap();
         assert_eq!(card, expected);
         assert_eq!(rem, "");
     }
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_example_line() {
         let input = "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19";
-        let expected = Card {
-            id: 2,
-            winning_numbers: [13, 32, 20, 16, 61].iter().cloned().collect(),
-            collected_numbers: [61, 30, 68, 82, 17, 32, 24, 19].to_vec(),
-        };
+        let expected = Card::new(2, &[13, 32, 20, 16, 61], &[61, 30, 68, 82, 17, 32, 24, 19]);
 
         match parse_card(input) {
             Ok((rem, card)) => {
@@ -145,6 +210,23 @@ mod tests {
         assert_eq!(card.score(), expected_score);
     }
 
+    #[test]
+    fn test_card_duplicates() {
+        let input = "Card 3: 1 2 2 200 | 2 200 200 5 5";
+        let card = Card::from_line(input).unwrap();
+
+        let expected = vec![
+            Duplicate::Winning(2),
+            Duplicate::Collected(200),
+            Duplicate::Collected(5),
+        ];
+        assert_eq!(card.duplicates, expected);
+
+        // The repeated 200 is counted twice
+        assert_eq!(card.n_matches(), 3);
+        assert_eq!(card.score(), 4);
+    }
+
     #[test]
     fn test_parse_lines() {
         let input = indoc!{"
@@ -0,0 +1,99 @@
+/// A set of card numbers.
+///
+/// Numbers below 128 are stored in a bitset so that the overlap of two sets is a single
+/// popcount, any larger numbers fall back to a sorted vector.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct NumberSet {
+    low: u128,
+    high: Vec<u32>,
+}
+
+impl NumberSet {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert the number, returning false if it was already in the set
+    pub(crate) fn insert(&mut self, number: u32) -> bool {
+        if number < u128::BITS {
+            let bit = 1u128 << number;
+            let is_new = self.low & bit == 0;
+            self.low |= bit;
+            is_new
+        } else {
+            match self.high.binary_search(&number) {
+                Ok(_) => false,
+                Err(idx) => {
+                    self.high.insert(idx, number);
+                    true
+                }
+            }
+        }
+    }
+
+    pub(crate) fn contains(&self, number: u32) -> bool {
+        if number < u128::BITS {
+            self.low & (1u128 << number) != 0
+        } else {
+            self.high.binary_search(&number).is_ok()
+        }
+    }
+
+    /// Returns the number of values that are in both sets
+    pub(crate) fn count_common(&self, other: &NumberSet) -> u32 {
+        let low = (self.low & other.low).count_ones();
+
+        // Both of the fallback vectors are sorted, so we can walk them together
+        let (mut i, mut j, mut high) = (0, 0, 0);
+        while i < self.high.len() && j < other.high.len() {
+            match self.high[i].cmp(&other.high[j]) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    high += 1;
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+
+        low + high
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn from_slice(numbers: &[u32]) -> NumberSet {
+        let mut set = NumberSet::new();
+        numbers.iter().for_each(|&n| {
+            set.insert(n);
+        });
+        set
+    }
+
+    #[test]
+    fn test_insert_and_contains() {
+        let mut set = NumberSet::new();
+        for n in [0, 5, 127, 128, 1000] {
+            assert!(!set.contains(n));
+            assert!(set.insert(n));
+            assert!(set.contains(n));
+        }
+
+        // Duplicates are reported on both sides of the bitset
+        assert!(!set.insert(5));
+        assert!(!set.insert(1000));
+        assert!(set.contains(5) && set.contains(1000));
+    }
+
+    #[test]
+    fn test_count_common() {
+        let a = from_slice(&[1, 2, 3, 127, 128, 500, 9000]);
+        let b = from_slice(&[3, 4, 127, 500, 501, 9000]);
+        assert_eq!(a.count_common(&b), 4);
+        assert_eq!(b.count_common(&a), 4);
+        assert_eq!(a.count_common(&NumberSet::new()), 0);
+    }
+}
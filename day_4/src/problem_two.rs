@@ -0,0 +1,117 @@
+use crate::{parse_lines, Card};
+use std::fmt::Display;
+
+pub(crate) fn problem_two() -> u64 {
+    let input = include_str!("problem_text");
+    get_total_cards(input).expect("Failed to count the cards")
+}
+
+/// Returned when the number of copies of a card no longer fits in a `u64`
+#[derive(Debug, PartialEq)]
+pub(crate) struct CopyOverflow {
+    card_id: u32,
+}
+
+impl Display for CopyOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the number of copies overflowed at card {}",
+            self.card_id
+        )
+    }
+}
+
+/// Returns the total number of cards held once every copy has been processed
+fn get_total_cards(lines: &str) -> Result<u64, CopyOverflow> {
+    let cards = parse_lines(lines);
+    let copies = get_copy_counts(&cards)?;
+
+    copies
+        .iter()
+        .zip(cards.iter())
+        .try_fold(0u64, |acc, (&n, card)| {
+            acc.checked_add(n).ok_or(CopyOverflow { card_id: card.id })
+        })
+}
+
+/// Returns the number of copies held of each card, including the original.
+///
+/// Each card with `n` matches wins a copy of the next `n` cards for every copy that is
+/// held, any wins that run past the end of the table are discarded.
+pub(crate) fn get_copy_counts(cards: &[Card]) -> Result<Vec<u64>, CopyOverflow> {
+    let mut copies = vec![1u64; cards.len()];
+
+    for (idx, card) in cards.iter().enumerate() {
+        let n_matches = card.n_matches() as usize;
+        let current = copies[idx];
+        let end = (idx + 1 + n_matches).min(cards.len());
+
+        for (offset, count) in copies[idx + 1..end].iter_mut().enumerate() {
+            *count = count.checked_add(current).ok_or(CopyOverflow {
+                card_id: cards[idx + 1 + offset].id,
+            })?;
+        }
+    }
+
+    Ok(copies)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    const SAMPLE: &str = indoc! {"
+        Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+        Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+        Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+        Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+        Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+    "};
+
+    /// Generate a table where every card matches `n_matches` numbers
+    fn generate_cards(n_cards: u32, n_matches: u32) -> Vec<Card> {
+        (1..=n_cards)
+            .map(|id| {
+                let numbers = (1..=n_matches).collect::<Vec<_>>();
+                Card::new(id, &numbers, &numbers)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sample_total() {
+        assert_eq!(get_total_cards(SAMPLE), Ok(30));
+    }
+
+    #[test]
+    fn test_sample_copy_counts() {
+        let cards = parse_lines(SAMPLE);
+        let expected = vec![1, 2, 4, 8, 14, 1];
+        assert_eq!(get_copy_counts(&cards).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_cascade_past_end() {
+        // The last cards win more copies than there are cards remaining
+        let cards = generate_cards(4, 10);
+        let expected = vec![1, 2, 4, 8];
+        assert_eq!(get_copy_counts(&cards).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_copy_overflow() {
+        // Every card wins a copy of every following card, so the counts double each time
+        let cards = generate_cards(70, 70);
+        assert_eq!(get_copy_counts(&cards), Err(CopyOverflow { card_id: 65 }));
+
+        // The largest table where the total, 2^64 - 1, still fits
+        let cards = generate_cards(64, 64);
+        let copies = get_copy_counts(&cards).unwrap();
+        assert_eq!(copies[63], 1 << 63);
+        let total = copies.iter().try_fold(0u64, |acc, &n| acc.checked_add(n));
+        assert_eq!(total, Some(u64::MAX));
+    }
+}
@@ -0,0 +1,398 @@
+use crate::{parse_input, Layout, Race};
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+
+/// Print the best hold time and winning range of each race in the problem text for a
+/// selection of boat models.
+pub(crate) fn model_report() {
+    let input = include_str!("problem_text");
+    let races = parse_input(input, Layout::Spaced)
+        .unwrap_or_else(|e| panic!("Failed to parse input: {}", e));
+
+    let models = [
+        BoatModel::Linear { acceleration: 1 },
+        BoatModel::Linear { acceleration: 2 },
+        BoatModel::Capped {
+            acceleration: 1,
+            max_speed: 20,
+        },
+        BoatModel::Drag {
+            acceleration: 2,
+            drag: 1,
+        },
+        BoatModel::Diminishing {
+            acceleration: 10,
+            falloff: 1,
+        },
+    ];
+
+    for model in models.iter() {
+        println!("{}", model);
+        for race in races.iter() {
+            let report = race.get_report(model);
+            let interval = match &report.interval {
+                Some(interval) => format!("{}..={}", interval.start(), interval.end()),
+                None => "none".to_string(),
+            };
+            println!(
+                "  Race {:>4}ms / {:>6}mm: hold {:>3}ms for {:>6}mm, margin {:>6}mm, winning {}",
+                race.time,
+                race.distance,
+                report.optimal_hold,
+                report.best_distance,
+                report.margin,
+                interval
+            );
+        }
+    }
+}
+
+/// How the speed of the boat depends on the time the button is held, and how the boat
+/// moves once it is released.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum BoatModel {
+    /// Each millisecond of holding adds `acceleration` to the speed
+    Linear { acceleration: u128 },
+    /// As `Linear`, but the speed cannot go above `max_speed`
+    Capped { acceleration: u128, max_speed: u128 },
+    /// As `Linear`, but the boat loses `drag` speed every millisecond that it moves
+    Drag { acceleration: u128, drag: u128 },
+    /// The first millisecond of holding adds `acceleration` to the speed, and each one
+    /// after adds `falloff` less than the last, down to zero
+    Diminishing { acceleration: u128, falloff: u128 },
+}
+
+impl Display for BoatModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoatModel::Linear { acceleration } => {
+                write!(f, "Linear, acceleration {}", acceleration)
+            }
+            BoatModel::Capped {
+                acceleration,
+                max_speed,
+            } => write!(
+                f,
+                "Capped, acceleration {}, max speed {}",
+                acceleration, max_speed
+            ),
+            BoatModel::Drag { acceleration, drag } => {
+                write!(f, "Drag, acceleration {}, drag {}", acceleration, drag)
+            }
+            BoatModel::Diminishing {
+                acceleration,
+                falloff,
+            } => write!(
+                f,
+                "Diminishing, acceleration {}, falloff {}",
+                acceleration, falloff
+            ),
+        }
+    }
+}
+
+impl BoatModel {
+    /// Returns the speed after holding the button, `None` if it overflows
+    pub(crate) fn get_speed(&self, hold_time: u128) -> Option<u128> {
+        match *self {
+            BoatModel::Linear { acceleration } | BoatModel::Drag { acceleration, .. } => {
+                acceleration.checked_mul(hold_time)
+            }
+            BoatModel::Capped {
+                acceleration,
+                max_speed,
+            } => Some(
+                acceleration
+                    .checked_mul(hold_time)
+                    .map_or(max_speed, |s| s.min(max_speed)),
+            ),
+            BoatModel::Diminishing {
+                acceleration,
+                falloff,
+            } => {
+                // Only the first `n` milliseconds add any speed
+                let n = match falloff {
+                    0 => hold_time,
+                    _ => hold_time.min(acceleration.div_ceil(falloff)),
+                };
+                arithmetic_sum(acceleration, falloff, n)
+            }
+        }
+    }
+
+    /// Returns the distance travelled in the race, `None` if it overflows
+    pub(crate) fn get_distance(&self, hold_time: u128, total_time: u128) -> Option<u128> {
+        assert!(hold_time <= total_time);
+        let speed = self.get_speed(hold_time)?;
+        let moving_time = total_time - hold_time;
+
+        match *self {
+            BoatModel::Drag { drag, .. } if drag > 0 => {
+                // The boat stops once the drag has taken away all of its speed
+                let n = moving_time.min(speed.div_ceil(drag));
+                arithmetic_sum(speed, drag, n)
+            }
+            _ => speed.checked_mul(moving_time),
+        }
+    }
+
+    /// Returns true if the winning hold times can be found without searching
+    fn is_analytic(&self) -> bool {
+        matches!(self, BoatModel::Linear { .. } | BoatModel::Capped { .. })
+    }
+}
+
+/// The sum of `n` terms of `first`, `first - step`, `first - 2 * step`...
+fn arithmetic_sum(first: u128, step: u128, n: u128) -> Option<u128> {
+    if n == 0 {
+        return Some(0);
+    }
+    // n * (n - 1) is always even, so halve whichever factor is even to avoid overflow
+    let triangle = if n.is_multiple_of(2) {
+        (n / 2).checked_mul(n - 1)?
+    } else {
+        n.checked_mul((n - 1) / 2)?
+    };
+    first
+        .checked_mul(n)?
+        .checked_sub(step.checked_mul(triangle)?)
+}
+
+/// The result of racing with a boat model
+#[derive(Debug, PartialEq)]
+pub(crate) struct ModelReport {
+    pub(crate) interval: Option<RangeInclusive<u128>>,
+    pub(crate) optimal_hold: u128,
+    pub(crate) best_distance: u128,
+    /// How far the best distance beats the record by, zero if it does not
+    pub(crate) margin: u128,
+}
+
+impl Race {
+    pub(crate) fn get_report(&self, model: &BoatModel) -> ModelReport {
+        let optimal_hold = self.get_optimal_hold(model);
+        let best_distance = self.distance_or_max(model, optimal_hold);
+
+        ModelReport {
+            interval: self.get_model_interval(model),
+            optimal_hold,
+            best_distance,
+            margin: best_distance.saturating_sub(self.distance),
+        }
+    }
+
+    /// Returns the range of hold times that beat the record with this boat model.
+    ///
+    /// The linear and capped models are solved exactly, the others use a search that
+    /// relies on the distance rising to a single peak and then falling.
+    pub(crate) fn get_model_interval(&self, model: &BoatModel) -> Option<RangeInclusive<u128>> {
+        if model.is_analytic() {
+            return self.get_analytic_interval(model);
+        }
+
+        let optimal_hold = self.get_optimal_hold(model);
+        if !self.is_model_winning(model, optimal_hold) {
+            return None;
+        }
+
+        // The distance is increasing before the peak and decreasing after it
+        let lower = partition_point(0, optimal_hold, |h| !self.is_model_winning(model, h));
+        let upper = partition_point(optimal_hold, self.time + 1, |h| {
+            self.is_model_winning(model, h)
+        }) - 1;
+        Some(lower..=upper)
+    }
+
+    fn get_analytic_interval(&self, model: &BoatModel) -> Option<RangeInclusive<u128>> {
+        match *model {
+            BoatModel::Linear { acceleration } => {
+                if acceleration == 0 {
+                    return None;
+                }
+                // a * x > d is the same as x > floor(d / a) for integers
+                Race::new(self.time, self.distance / acceleration).get_winning_interval()
+            }
+            BoatModel::Capped {
+                acceleration,
+                max_speed,
+            } => {
+                // min(a * h, c) * (t - h) > d needs both a * h * (t - h) > d and
+                // c * (t - h) > d, the second only limits the longest hold time
+                let linear = self.get_analytic_interval(&BoatModel::Linear { acceleration })?;
+                if max_speed == 0 {
+                    return None;
+                }
+                let capped_end = self.time.checked_sub(self.distance / max_speed + 1)?;
+                let end = (*linear.end()).min(capped_end);
+                (*linear.start() <= end).then(|| *linear.start()..=end)
+            }
+            _ => unreachable!("{} does not have an analytic solution", model),
+        }
+    }
+
+    /// Returns the hold time that travels the furthest, the shortest if there is a tie
+    pub(crate) fn get_optimal_hold(&self, model: &BoatModel) -> u128 {
+        // Ternary search, this relies on the distance having a single peak
+        let (mut lo, mut hi) = (0, self.time);
+        while hi - lo > 2 {
+            let m1 = lo + (hi - lo) / 3;
+            let m2 = hi - (hi - lo) / 3;
+            let (d1, d2) = (
+                self.distance_or_max(model, m1),
+                self.distance_or_max(model, m2),
+            );
+            match d1.cmp(&d2) {
+                std::cmp::Ordering::Less => lo = m1 + 1,
+                std::cmp::Ordering::Greater => hi = m2 - 1,
+                std::cmp::Ordering::Equal => {
+                    lo = m1;
+                    hi = m2;
+                }
+            }
+        }
+
+        (lo..=hi)
+            .rev()
+            .max_by_key(|&h| self.distance_or_max(model, h))
+            .expect("Search range is never empty")
+    }
+
+    fn is_model_winning(&self, model: &BoatModel, hold_time: u128) -> bool {
+        self.distance_or_max(model, hold_time) > self.distance
+    }
+
+    /// An overflowing distance is certainly larger than any record
+    fn distance_or_max(&self, model: &BoatModel, hold_time: u128) -> u128 {
+        model
+            .get_distance(hold_time, self.time)
+            .unwrap_or(u128::MAX)
+    }
+}
+
+/// Returns the first value in `lo..hi` for which the predicate is false, the predicate
+/// must be true for every value before this and false for every value after.
+fn partition_point(mut lo: u128, mut hi: u128, pred: impl Fn(u128) -> bool) -> u128 {
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Returns the winning interval and best distance by trying every hold time
+    fn brute_force(race: &Race, model: &BoatModel) -> (Option<RangeInclusive<u128>>, u128) {
+        let distances = (0..=race.time)
+            .map(|h| race.distance_or_max(model, h))
+            .collect::<Vec<_>>();
+        let winners = (0..=race.time)
+            .filter(|&h| distances[h as usize] > race.distance)
+            .collect::<Vec<_>>();
+
+        // The winning hold times should never have a gap
+        if let (Some(&first), Some(&last)) = (winners.first(), winners.last()) {
+            assert_eq!(winners.len() as u128, last - first + 1);
+        }
+
+        let interval = winners
+            .first()
+            .map(|&first| first..=*winners.last().unwrap());
+        (interval, *distances.iter().max().unwrap())
+    }
+
+    fn model_strategy() -> impl Strategy<Value = BoatModel> {
+        prop_oneof![
+            (0..5u128).prop_map(|acceleration| BoatModel::Linear { acceleration }),
+            (1..5u128, 1..200u128).prop_map(|(acceleration, max_speed)| BoatModel::Capped {
+                acceleration,
+                max_speed
+            }),
+            (1..5u128, 0..5u128)
+                .prop_map(|(acceleration, drag)| BoatModel::Drag { acceleration, drag }),
+            (1..50u128, 0..5u128).prop_map(|(acceleration, falloff)| BoatModel::Diminishing {
+                acceleration,
+                falloff
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_linear_matches_race() {
+        let race = Race::new(30, 200);
+        let model = BoatModel::Linear { acceleration: 1 };
+        let report = race.get_report(&model);
+
+        assert_eq!(report.interval, race.get_winning_interval());
+        assert_eq!(report.optimal_hold, 15);
+        assert_eq!(report.best_distance, 225);
+        assert_eq!(report.margin, 25);
+    }
+
+    #[test]
+    fn test_capped_interval() {
+        // The speed is capped at 10, so holding for longer than 10ms is wasted
+        let race = Race::new(30, 150);
+        let model = BoatModel::Capped {
+            acceleration: 1,
+            max_speed: 10,
+        };
+        assert_eq!(race.get_model_interval(&model), Some(7..=14));
+        assert_eq!(race.get_optimal_hold(&model), 10);
+    }
+
+    #[test]
+    fn test_model_distances() {
+        let drag = BoatModel::Drag {
+            acceleration: 2,
+            drag: 3,
+        };
+        // Speed 10, then 7, 4, 1 before stopping
+        assert_eq!(drag.get_distance(5, 100), Some(22));
+        // Only two milliseconds left to move
+        assert_eq!(drag.get_distance(5, 7), Some(17));
+
+        let diminishing = BoatModel::Diminishing {
+            acceleration: 5,
+            falloff: 2,
+        };
+        // The speed gains are 5, 3, 1, then nothing
+        assert_eq!(diminishing.get_speed(2), Some(8));
+        assert_eq!(diminishing.get_speed(10), Some(9));
+    }
+
+    #[test]
+    fn test_no_winning_times() {
+        let race = Race::new(10, 1000);
+        let model = BoatModel::Drag {
+            acceleration: 1,
+            drag: 1,
+        };
+        let report = race.get_report(&model);
+        assert_eq!(report.interval, None);
+        assert_eq!(report.margin, 0);
+    }
+
+    proptest! {
+        #[test]
+        fn test_models_match_brute_force(
+            model in model_strategy(),
+            time in 0..300u128,
+            distance in 0..5000u128,
+        ) {
+            let race = Race::new(time, distance);
+            let (interval, best) = brute_force(&race, &model);
+
+            prop_assert_eq!(race.get_model_interval(&model), interval);
+            let report = race.get_report(&model);
+            prop_assert_eq!(report.best_distance, best);
+        }
+    }
+}
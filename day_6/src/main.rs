@@ -1,71 +1,197 @@
+mod boat_model;
+
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+
 fn main() {
-    let input = include_str!("problem_text_one");
-    let solution_one = get_winning_product(input);
+    if std::env::args().nth(1).as_deref() == Some("models") {
+        boat_model::model_report();
+        return;
+    }
+
+    let input = include_str!("problem_text");
+    let solution_one = get_winning_product(input, Layout::Spaced);
     println!("Solution One: {}", solution_one);
 
-    let input = include_str!("problem_text_two");
-    let solution_two = get_winning_product(input);
+    let solution_two = get_winning_product(input, Layout::Kerned);
     println!("Solution Two: {}", solution_two);
 }
 
 #[derive(Debug, PartialEq)]
 struct Race {
-    time: u64,
-    distance: u64,
+    time: u128,
+    distance: u128,
 }
 
 impl Race {
-    fn new(time: u64, distance: u64) -> Self {
+    fn new(time: u128, distance: u128) -> Self {
         Self { time, distance }
     }
 
     // We could reduce this by only returning the length of the winning times
     // but this is more useful for debugging
-    fn get_winning_times(&self) -> Vec<u64> {
+    #[cfg(test)]
+    fn get_winning_times(&self) -> Vec<u128> {
         (1..self.time)
             .filter(|hold_time| get_distance_travelled(*hold_time, self.time) > self.distance)
             .collect()
     }
+
+    /// Returns the range of hold times that beat the record, if there are any.
+    ///
+    /// The winning hold times `h` satisfy `h * (time - h) > distance`, which lies between
+    /// the roots of `h^2 - time * h + distance = 0`. The roots are found with an integer
+    /// square root, then nudged onto the exact boundary, so there is no floating point
+    /// error even for `u128` inputs.
+    fn get_winning_interval(&self) -> Option<RangeInclusive<u128>> {
+        let half = self.time / 2;
+        if !self.is_winning(half) {
+            // The distance is largest at the midpoint, so nothing else can win
+            return None;
+        }
+
+        let discriminant = self
+            .time
+            .checked_mul(self.time)
+            .and_then(|t2| Some(t2 - self.distance.checked_mul(4)?));
+
+        let mut lower = match discriminant {
+            Some(d) => (self.time - d.isqrt()) / 2,
+            // The square does not fit, so search for the boundary instead
+            None => self.search_lower_bound(),
+        };
+
+        // The integer square root is within one of the true root
+        while !self.is_winning(lower) {
+            lower += 1;
+        }
+        while lower > 0 && self.is_winning(lower - 1) {
+            lower -= 1;
+        }
+
+        // The distance is symmetric about the midpoint
+        Some(lower..=self.time - lower)
+    }
+
+    /// Returns the number of hold times that beat the record
+    fn count_winning_times(&self) -> u128 {
+        self.get_winning_interval()
+            .map_or(0, |interval| interval.end() - interval.start() + 1)
+    }
+
+    fn is_winning(&self, hold_time: u128) -> bool {
+        // If the distance overflows then it is certainly larger than the record
+        checked_distance_travelled(hold_time, self.time).is_none_or(|d| d > self.distance)
+    }
+
+    /// Binary search for the first winning hold time below the midpoint
+    fn search_lower_bound(&self) -> u128 {
+        let (mut lo, mut hi) = (0, self.time / 2);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.is_winning(mid) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        lo
+    }
 }
 
-fn get_winning_product(input: &str) -> u64 {
-    let races = parse_input(input);
+fn get_winning_product(input: &str, layout: Layout) -> u128 {
+    let races =
+        parse_input(input, layout).unwrap_or_else(|e| panic!("Failed to parse input: {}", e));
 
-    races.into_iter().map(|r| r.get_winning_times().len() as u64).product()
+    races.into_iter().map(|r| r.count_winning_times()).product()
 }
 
-fn get_distance_travelled(hold_time: u64, total_time: u64) -> u64 {
+#[cfg(test)]
+fn get_distance_travelled(hold_time: u128, total_time: u128) -> u128 {
+    checked_distance_travelled(hold_time, total_time).expect("Distance overflowed")
+}
+
+fn checked_distance_travelled(hold_time: u128, total_time: u128) -> Option<u128> {
     assert!(hold_time <= total_time);
     let acceleration = 1;
-    acceleration * hold_time * (total_time - hold_time)
+    (acceleration * hold_time).checked_mul(total_time - hold_time)
+}
+
+/// How the columns of the race sheet should be read
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Layout {
+    /// Each column is a separate race
+    Spaced,
+    /// The spaces are ignored, so the digits of every column form a single race
+    Kerned,
+}
+
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    MissingLine(&'static str),
+    InvalidNumber(String),
+    ColumnMismatch { times: usize, distances: usize },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::MissingLine(label) => write!(f, "no line starting with {}", label),
+            ParseError::InvalidNumber(value) => write!(f, "invalid number {}", value),
+            ParseError::ColumnMismatch { times, distances } => {
+                write!(f, "found {} times but {} distances", times, distances)
+            }
+        }
+    }
 }
 
-fn parse_input(input: &str) -> Vec<Race> {
+fn parse_input(input: &str, layout: Layout) -> Result<Vec<Race>, ParseError> {
     let mut lines = input.lines();
-    let times = lines
-        .next()
-        .expect("No first line")
-        .split(' ')
-        .skip(1)
-        .filter_map(|v| v.parse::<u64>().ok());
-
-    let distances = lines
-        .next()
-        .expect("No second line")
-        .split(' ')
-        .skip(1)
-        .filter_map(|v| v.parse::<u64>().ok());
-
-    times
-        .zip(distances)
-        .map(|(time, distance)| Race::new(time, distance))
-        .collect()
+    let times = parse_columns(lines.next(), "Time:")?;
+    let distances = parse_columns(lines.next(), "Distance:")?;
+
+    if times.len() != distances.len() {
+        return Err(ParseError::ColumnMismatch {
+            times: times.len(),
+            distances: distances.len(),
+        });
+    }
+
+    match layout {
+        Layout::Spaced => times
+            .into_iter()
+            .zip(distances)
+            .map(|(time, distance)| Ok(Race::new(parse_number(time)?, parse_number(distance)?)))
+            .collect(),
+        Layout::Kerned => {
+            let time = parse_number(&times.concat())?;
+            let distance = parse_number(&distances.concat())?;
+            Ok(vec![Race::new(time, distance)])
+        }
+    }
+}
+
+/// Returns the columns of the line after the label
+fn parse_columns<'a>(
+    line: Option<&'a str>,
+    label: &'static str,
+) -> Result<Vec<&'a str>, ParseError> {
+    line.and_then(|line| line.strip_prefix(label))
+        .map(|rest| rest.split_whitespace().collect())
+        .ok_or(ParseError::MissingLine(label))
+}
+
+fn parse_number(value: &str) -> Result<u128, ParseError> {
+    value
+        .parse()
+        .map_err(|_| ParseError::InvalidNumber(value.to_string()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use indoc::indoc;
+    use proptest::prelude::*;
 
     #[test]
     fn parse_sample_input() {
@@ -75,11 +201,45 @@ mod tests {
         "};
         let expected = vec![Race::new(7, 9), Race::new(15, 40), Race::new(30, 200)];
 
-        let actual = parse_input(test_input);
+        let actual = parse_input(test_input, Layout::Spaced).unwrap();
 
         assert_eq!(actual, expected)
     }
 
+    #[test]
+    fn parse_sample_input_kerned() {
+        let test_input = indoc! {"
+            Time:      7  15   30
+            Distance:  9  40  200
+        "};
+        let expected = vec![Race::new(71530, 940200)];
+
+        let actual = parse_input(test_input, Layout::Kerned).unwrap();
+
+        assert_eq!(actual, expected)
+    }
+
+    #[test]
+    fn parse_invalid_input() {
+        let test_input = indoc! {"
+            Time:      7  15   30
+            Distance:  9  40
+        "};
+        let expected = Err(ParseError::ColumnMismatch {
+            times: 3,
+            distances: 2,
+        });
+        assert_eq!(parse_input(test_input, Layout::Kerned), expected);
+
+        let test_input = "Time: 7 15\nDistance: 9 4x";
+        let expected = Err(ParseError::InvalidNumber("4x".to_string()));
+        assert_eq!(parse_input(test_input, Layout::Spaced), expected);
+
+        let test_input = "Time: 7 15";
+        let expected = Err(ParseError::MissingLine("Distance:"));
+        assert_eq!(parse_input(test_input, Layout::Spaced), expected);
+    }
+
     #[test]
     fn get_race_times() {
         let race = Race::new(7, 9);
@@ -98,27 +258,73 @@ mod tests {
 
         let expected = 288;
 
-        let actual = get_winning_product(test_input);
+        let actual = get_winning_product(test_input, Layout::Spaced);
         assert_eq!(actual, expected)
     }
 
     #[test]
     fn test_sample_two() {
         let test_input = indoc! {"
-            Time:      71530
-            Distance:  940200
+            Time:      7  15   30
+            Distance:  9  40  200
         "};
 
         let expected = 71503;
 
-        let actual = get_winning_product(test_input);
+        let actual = get_winning_product(test_input, Layout::Kerned);
         assert_eq!(actual, expected)
     }
 
+    #[test]
+    fn test_winning_interval() {
+        assert_eq!(Race::new(7, 9).get_winning_interval(), Some(2..=5));
+        assert_eq!(Race::new(30, 200).get_winning_interval(), Some(11..=19));
+        assert_eq!(Race::new(71530, 940200).count_winning_times(), 71503);
+
+        // Exactly matching the record is not a win
+        assert_eq!(Race::new(4, 4).get_winning_interval(), None);
+        assert_eq!(Race::new(4, 3).get_winning_interval(), Some(2..=2));
+        assert_eq!(Race::new(0, 0).get_winning_interval(), None);
+    }
+
+    #[test]
+    fn test_winning_interval_u128() {
+        // The square of the time does not fit, so the boundary is found by search
+        let race = Race::new(u128::MAX, u128::MAX);
+        let interval = race.get_winning_interval().unwrap();
+        assert!(race.is_winning(*interval.start()));
+        assert!(!race.is_winning(*interval.start() - 1));
+        assert_eq!(*interval.end(), u128::MAX - interval.start());
+
+        // A perfect square boundary, h * (t - h) == d at h = 10^18
+        let h = 1_000_000_000_000_000_000u128;
+        let time = 3 * h;
+        let race = Race::new(time, h * (time - h));
+        assert_eq!(race.get_winning_interval(), Some(h + 1..=time - h - 1));
+    }
+
+    proptest! {
+        #[test]
+        fn test_interval_matches_brute_force(time in 0..500u128, frac in 0.0..1.1f64) {
+            let distance = ((time * time / 4) as f64 * frac) as u128;
+            let race = Race::new(time, distance);
+            let winners = race.get_winning_times();
+
+            match race.get_winning_interval() {
+                Some(interval) => {
+                    prop_assert_eq!(winners.first(), Some(interval.start()));
+                    prop_assert_eq!(winners.last(), Some(interval.end()));
+                    prop_assert_eq!(winners.len() as u128, race.count_winning_times());
+                }
+                None => prop_assert!(winners.is_empty()),
+            }
+        }
+    }
+
     #[test]
     fn test_problem_two() {
-        let test_input = include_str!("problem_text_two");
-        let mut races = parse_input(test_input);
+        let test_input = include_str!("problem_text");
+        let mut races = parse_input(test_input, Layout::Kerned).unwrap();
 
         assert_eq!(races.len(), 1);
 
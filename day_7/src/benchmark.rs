@@ -0,0 +1,79 @@
+use crate::rule_set::CamelCards;
+use crate::sort_key::radix_sort;
+use crate::{Card, Hand};
+use std::time::Instant;
+use xorshift::XorShift;
+
+/// Compare sorting a generated tournament with the `Hand` comparator against sorting the
+/// packed keys with a radix sort.
+pub(crate) fn benchmark(n_hands: usize) {
+    let mut rng = XorShift::new(0x5eed);
+    let bets = (0..n_hands)
+        .map(|_| generate_bet(&mut rng))
+        .collect::<Vec<_>>();
+
+    let mut naive = bets
+        .iter()
+        .map(|(hand, bid)| {
+            (
+                Hand::from_cards(hand.cards.clone(), vec![], &CamelCards),
+                *bid,
+            )
+        })
+        .collect::<Vec<_>>();
+    let start = Instant::now();
+    naive.sort_by(|(hand_a, _), (hand_b, _)| hand_a.cmp(hand_b));
+    let naive_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut keys = bets
+        .iter()
+        .map(|(hand, bid)| (hand.sort_key(), *bid))
+        .collect::<Vec<_>>();
+    let pack_time = start.elapsed();
+
+    let mut unstable = keys.clone();
+    let start = Instant::now();
+    unstable.sort_unstable_by_key(|(key, _)| *key);
+    let unstable_time = start.elapsed();
+
+    let start = Instant::now();
+    radix_sort(&mut keys);
+    let radix_time = start.elapsed();
+
+    assert!(
+        naive
+            .iter()
+            .map(|(_, bid)| bid)
+            .eq(keys.iter().map(|(_, bid)| bid)),
+        "Radix sort gave a different order"
+    );
+
+    println!("Hands: {}", n_hands);
+    println!("Comparator sort:       {:>10.2?}", naive_time);
+    println!("Packing keys:          {:>10.2?}", pack_time);
+    println!("Unstable key sort:     {:>10.2?}", unstable_time);
+    println!("Radix key sort:        {:>10.2?}", radix_time);
+}
+
+fn generate_bet(rng: &mut XorShift) -> (Hand, u32) {
+    const CARDS: [Card; 13] = [
+        Card::Unit(2),
+        Card::Unit(3),
+        Card::Unit(4),
+        Card::Unit(5),
+        Card::Unit(6),
+        Card::Unit(7),
+        Card::Unit(8),
+        Card::Unit(9),
+        Card::Ten,
+        Card::Jack,
+        Card::Queen,
+        Card::King,
+        Card::Ace,
+    ];
+
+    let cards = (0..5).map(|_| CARDS[rng.below(CARDS.len())]).collect();
+    let bid = 1 + rng.below(1000) as u32;
+    (Hand::from_cards(cards, vec![], &CamelCards), bid)
+}
@@ -0,0 +1,387 @@
+use crate::rule_set::{distinct_cards, RuleSet};
+use crate::{count_cards, Card, Hand, HandType};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use xorshift::XorShift;
+
+/// Play a tournament between the built-in strategies and print the statistics
+pub(crate) fn tournament_report(rules: &dyn RuleSet, n_games: u32, seed: u64) {
+    let strategies: Vec<Box<dyn Strategy>> =
+        vec![Box::new(Passive), Box::new(KeepGroups), Box::new(Gambler)];
+    let mut table = Table::new(rules, strategies, seed);
+    let stats = table.play_tournament(n_games);
+    println!("{} ({} games, seed {})", rules.name(), n_games, seed);
+    print!("{}", stats);
+}
+
+/// Number of copies of each card in the deck
+const COPIES: usize = 4;
+/// Number of cards that can be exchanged in the draw round
+const MAX_DRAW: usize = 3;
+/// The largest bid a strategy can make in a game
+const MAX_BID: u32 = 10;
+
+/// A shuffled deck of cards that are dealt from the top
+pub(crate) struct Deck {
+    cards: Vec<Card>,
+}
+
+impl Deck {
+    /// A full deck with four of each card, as read by the rule set
+    pub(crate) fn new(rules: &dyn RuleSet) -> Deck {
+        let cards = distinct_cards(rules)
+            .unwrap_or_else(|| panic!("{} cannot be dealt without suits", rules.name()))
+            .into_iter()
+            .flat_map(|card| std::iter::repeat_n(card, COPIES))
+            .collect();
+        Deck { cards }
+    }
+
+    pub(crate) fn shuffle(&mut self, rng: &mut XorShift) {
+        rng.shuffle(&mut self.cards);
+    }
+
+    /// Take `n` cards from the top of the deck, `None` if there are not enough left
+    pub(crate) fn deal(&mut self, n: usize) -> Option<Vec<Card>> {
+        let remaining = self.cards.len().checked_sub(n)?;
+        Some(self.cards.split_off(remaining))
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.cards.len()
+    }
+}
+
+/// Decides which cards to exchange and how much to bid on the final hand
+pub(crate) trait Strategy {
+    fn name(&self) -> &'static str;
+
+    /// Returns the positions of the cards to exchange, at most `MAX_DRAW` are used
+    fn discard(&self, hand: &Hand, rules: &dyn RuleSet, rng: &mut XorShift) -> Vec<usize>;
+
+    /// Returns the bid on the final hand, this is capped at `MAX_BID`
+    fn bid(&self, hand: &Hand, rng: &mut XorShift) -> u32;
+}
+
+/// Never exchanges any cards and always bids one
+pub(crate) struct Passive;
+
+impl Strategy for Passive {
+    fn name(&self) -> &'static str {
+        "Passive"
+    }
+
+    fn discard(&self, _hand: &Hand, _rules: &dyn RuleSet, _rng: &mut XorShift) -> Vec<usize> {
+        vec![]
+    }
+
+    fn bid(&self, _hand: &Hand, _rng: &mut XorShift) -> u32 {
+        1
+    }
+}
+
+/// Keeps every card that is part of a group, or a wildcard, and exchanges the rest,
+/// lowest first. Bids more with a better hand type.
+pub(crate) struct KeepGroups;
+
+impl Strategy for KeepGroups {
+    fn name(&self) -> &'static str {
+        "Keep groups"
+    }
+
+    fn discard(&self, hand: &Hand, rules: &dyn RuleSet, _rng: &mut XorShift) -> Vec<usize> {
+        let counter = count_cards(&hand.cards);
+        let mut singles = hand
+            .cards
+            .iter()
+            .enumerate()
+            .filter(|(_, card)| counter[card] == 1 && !rules.is_wildcard(**card))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+
+        singles.sort_by_key(|&idx| hand.ranks[idx]);
+        singles
+    }
+
+    fn bid(&self, hand: &Hand, _rng: &mut XorShift) -> u32 {
+        1 + hand.hand_type as u32
+    }
+}
+
+/// Exchanges a random number of random cards and bids at random
+pub(crate) struct Gambler;
+
+impl Strategy for Gambler {
+    fn name(&self) -> &'static str {
+        "Gambler"
+    }
+
+    fn discard(&self, hand: &Hand, _rules: &dyn RuleSet, rng: &mut XorShift) -> Vec<usize> {
+        let mut positions = (0..hand.cards.len()).collect::<Vec<_>>();
+        rng.shuffle(&mut positions);
+        positions.truncate(rng.below(MAX_DRAW + 1));
+        positions
+    }
+
+    fn bid(&self, _hand: &Hand, rng: &mut XorShift) -> u32 {
+        1 + rng.below(MAX_BID as usize) as u32
+    }
+}
+
+/// A table of players, one for each strategy
+pub(crate) struct Table<'a> {
+    rules: &'a dyn RuleSet,
+    strategies: Vec<Box<dyn Strategy>>,
+    rng: XorShift,
+}
+
+/// The result of a single game
+#[derive(Debug, PartialEq)]
+pub(crate) struct GameResult {
+    pub(crate) hands: Vec<Hand>,
+    pub(crate) bids: Vec<u32>,
+    /// The players with the best hand, the pot is split between them
+    pub(crate) winners: Vec<usize>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct PlayerStats {
+    pub(crate) name: &'static str,
+    pub(crate) wins: u32,
+    /// Net chips won over the tournament
+    pub(crate) chips: i64,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct TournamentStats {
+    pub(crate) games: u32,
+    pub(crate) players: Vec<PlayerStats>,
+    /// How often each type of final hand was held
+    pub(crate) hand_types: BTreeMap<HandType, u32>,
+}
+
+impl Display for TournamentStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for player in self.players.iter() {
+            let win_rate = 100.0 * player.wins as f64 / self.games.max(1) as f64;
+            writeln!(
+                f,
+                "{:<12} wins {:>6} ({:>5.1}%), chips {:>+8}",
+                player.name, player.wins, win_rate, player.chips
+            )?;
+        }
+
+        let total_hands = self.hand_types.values().sum::<u32>().max(1);
+        for (hand_type, count) in self.hand_types.iter() {
+            let frequency = 100.0 * *count as f64 / total_hands as f64;
+            let name = format!("{:?}", hand_type);
+            writeln!(f, "{:<14} {:>8} ({:>6.3}%)", name, count, frequency)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Table<'a> {
+    pub(crate) fn new(
+        rules: &'a dyn RuleSet,
+        strategies: Vec<Box<dyn Strategy>>,
+        seed: u64,
+    ) -> Table<'a> {
+        let cards_needed = strategies.len() * (rules.hand_size() + MAX_DRAW);
+        assert!(
+            cards_needed <= Deck::new(rules).len(),
+            "Not enough cards for {} players",
+            strategies.len()
+        );
+
+        Table {
+            rules,
+            strategies,
+            rng: XorShift::new(seed),
+        }
+    }
+
+    /// Deal a hand to every player, let them exchange cards, then collect the bids
+    pub(crate) fn play_game(&mut self) -> GameResult {
+        let mut deck = Deck::new(self.rules);
+        deck.shuffle(&mut self.rng);
+
+        let mut hands = Vec::with_capacity(self.strategies.len());
+        for strategy in self.strategies.iter() {
+            let cards = deck
+                .deal(self.rules.hand_size())
+                .expect("Deck is large enough");
+            let hand = Hand::from_cards(cards, vec![], self.rules);
+            hands.push(Self::draw(
+                self.rules,
+                strategy.as_ref(),
+                hand,
+                &mut deck,
+                &mut self.rng,
+            ));
+        }
+
+        let bids = self
+            .strategies
+            .iter()
+            .zip(hands.iter())
+            .map(|(strategy, hand)| strategy.bid(hand, &mut self.rng).clamp(1, MAX_BID))
+            .collect();
+
+        let best = hands.iter().max().expect("There is at least one player");
+        let winners = (0..hands.len()).filter(|&i| hands[i] == *best).collect();
+
+        GameResult {
+            hands,
+            bids,
+            winners,
+        }
+    }
+
+    /// Replace the cards that the strategy discards with new ones from the deck
+    fn draw(
+        rules: &dyn RuleSet,
+        strategy: &dyn Strategy,
+        hand: Hand,
+        deck: &mut Deck,
+        rng: &mut XorShift,
+    ) -> Hand {
+        let mut discards = strategy.discard(&hand, rules, rng);
+        discards.retain(|&idx| idx < hand.cards.len());
+        discards.sort_unstable();
+        discards.dedup();
+        discards.truncate(MAX_DRAW);
+
+        if discards.is_empty() {
+            return hand;
+        }
+
+        let mut cards = hand.cards;
+        let new_cards = deck.deal(discards.len()).expect("Deck is large enough");
+        for (idx, card) in discards.into_iter().zip(new_cards) {
+            cards[idx] = card;
+        }
+        Hand::from_cards(cards, vec![], rules)
+    }
+
+    pub(crate) fn play_tournament(&mut self, n_games: u32) -> TournamentStats {
+        let mut players = self
+            .strategies
+            .iter()
+            .map(|strategy| PlayerStats {
+                name: strategy.name(),
+                wins: 0,
+                chips: 0,
+            })
+            .collect::<Vec<_>>();
+        let mut hand_types = BTreeMap::new();
+
+        for _ in 0..n_games {
+            let result = self.play_game();
+
+            // Every player pays their bid into the pot, which the winners split
+            let pot = result.bids.iter().sum::<u32>() as i64;
+            let share = pot / result.winners.len() as i64;
+            for (player, bid) in players.iter_mut().zip(result.bids.iter()) {
+                player.chips -= *bid as i64;
+            }
+            for &winner in result.winners.iter() {
+                players[winner].wins += 1;
+                players[winner].chips += share;
+            }
+
+            for hand in result.hands.iter() {
+                *hand_types.entry(hand.hand_type).or_insert(0) += 1;
+            }
+        }
+
+        TournamentStats {
+            games: n_games,
+            players,
+            hand_types,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rule_set::{CamelCards, CamelCardsJokers};
+
+    fn all_strategies() -> Vec<Box<dyn Strategy>> {
+        vec![Box::new(Passive), Box::new(KeepGroups), Box::new(Gambler)]
+    }
+
+    #[test]
+    fn test_deck() {
+        let mut deck = Deck::new(&CamelCardsJokers);
+        assert_eq!(deck.len(), 52);
+
+        let mut rng = XorShift::new(7);
+        deck.shuffle(&mut rng);
+        let mut cards = deck.deal(52).unwrap();
+        assert_eq!(deck.deal(1), None);
+
+        // Shuffling only changes the order
+        cards.sort();
+        assert_eq!(count_cards(&cards).len(), 13);
+        assert!(count_cards(&cards).values().all(|&n| n == 4));
+        assert_eq!(cards[0], Card::Joker);
+    }
+
+    #[test]
+    fn test_keep_groups_discard() {
+        let hand = Hand::from_string("K3T3A", &CamelCards);
+        // Lowest singles first, the pair of threes is kept
+        assert_eq!(
+            KeepGroups.discard(&hand, &CamelCards, &mut XorShift::new(1)),
+            vec![2, 0, 4]
+        );
+
+        let hand = Hand::from_string("KJT3J", &CamelCardsJokers);
+        assert_eq!(
+            KeepGroups.discard(&hand, &CamelCardsJokers, &mut XorShift::new(1)),
+            vec![3, 2, 0]
+        );
+    }
+
+    #[test]
+    fn test_tournament_is_repeatable() {
+        let stats_a = Table::new(&CamelCards, all_strategies(), 42).play_tournament(200);
+        let stats_b = Table::new(&CamelCards, all_strategies(), 42).play_tournament(200);
+        assert_eq!(stats_a, stats_b);
+
+        let stats_c = Table::new(&CamelCards, all_strategies(), 43).play_tournament(200);
+        assert_ne!(stats_a, stats_c);
+    }
+
+    #[test]
+    fn test_tournament_totals() {
+        let n_games = 500;
+        let stats = Table::new(&CamelCardsJokers, all_strategies(), 1).play_tournament(n_games);
+
+        // Every player holds a hand in every game
+        let n_hands = stats.hand_types.values().sum::<u32>();
+        assert_eq!(n_hands, n_games * 3);
+
+        // Chips are only lost when a split pot does not divide evenly
+        let net = stats.players.iter().map(|p| p.chips).sum::<i64>();
+        assert!(net <= 0 && net > -(n_games as i64) * 2);
+
+        let wins = stats.players.iter().map(|p| p.wins).sum::<u32>();
+        assert!(wins >= n_games);
+    }
+
+    #[test]
+    fn test_game_winner() {
+        let mut table = Table::new(&CamelCards, all_strategies(), 3);
+        let result = table.play_game();
+
+        assert_eq!(result.hands.len(), 3);
+        let best = result.hands.iter().max().unwrap();
+        for &winner in result.winners.iter() {
+            assert_eq!(&result.hands[winner], best);
+        }
+        assert!(result.bids.iter().all(|&bid| (1..=MAX_BID).contains(&bid)));
+    }
+}
@@ -1,31 +1,103 @@
+mod benchmark;
+mod engine;
+mod probability;
+mod rule_set;
+mod sort_key;
+
+use rule_set::{CamelCards, CamelCardsJokers, RuleSet, Suit};
 use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [command, rest @ ..] if command == "bench" => {
+            let n_hands = rest
+                .first()
+                .map_or(1_000_000, |n| n.parse().expect("Invalid number of hands"));
+            benchmark::benchmark(n_hands);
+            return;
+        }
+        [command, rest @ ..] if command == "tournament" => {
+            let rules = rest.first().map_or("camel", String::as_str);
+            let Some(rules) = rule_set::from_name(rules) else {
+                eprintln!(
+                    "Unknown rule set {}, expected one of: camel, jokers, poker",
+                    rules
+                );
+                return;
+            };
+            // Dealing needs a deck, which can't be made for rule sets whose cards have suits
+            if rule_set::distinct_cards(rules.as_ref()).is_none() {
+                eprintln!("{} hands cannot be dealt without suits", rules.name());
+                return;
+            }
+            let n_games = rest
+                .get(1)
+                .map_or(10_000, |n| n.parse().expect("Invalid number of games"));
+            let seed = rest.get(2).map_or(1, |n| n.parse().expect("Invalid seed"));
+            engine::tournament_report(rules.as_ref(), n_games, seed);
+            return;
+        }
+        [command, rest @ ..] if command == "odds" => {
+            let rules = rest.first().map_or("camel", String::as_str);
+            let Some(rules) = rule_set::from_name(rules) else {
+                eprintln!(
+                    "Unknown rule set {}, expected one of: camel, jokers, poker",
+                    rules
+                );
+                return;
+            };
+            // Hands are enumerated from the distinct cards, which rule sets with suits don't have
+            if rule_set::distinct_cards(rules.as_ref()).is_none() {
+                eprintln!("{} hands cannot be enumerated without suits", rules.name());
+                return;
+            }
+            probability::odds_report(rules.as_ref(), rest.get(1).map(String::as_str));
+            return;
+        }
+        [rules, path] => {
+            let Some(rules) = rule_set::from_name(rules) else {
+                eprintln!(
+                    "Unknown rule set {}, expected one of: camel, jokers, poker",
+                    rules
+                );
+                return;
+            };
+            let input = std::fs::read_to_string(path).expect("Failed to read input file");
+            println!("Total: {}", get_bet_total_naive(&input, rules.as_ref()));
+            return;
+        }
+        _ => {}
+    }
+
     let input = include_str!("problem_text");
-    let problem_one = get_bet_total_naive(input);
+    let problem_one = get_bet_total_naive(input, &CamelCards);
     let expected_one = 252295678;
 
     println!("Total: {}", problem_one);
     if problem_one != expected_one {
         println!("Expected: {}", expected_one);
     }
+
+    let problem_two = get_bet_total_naive(input, &CamelCardsJokers);
+    println!("Total with jokers: {}", problem_two);
 }
 
-fn parse_input(input: &str) -> Vec<(Hand, u32)> {
+fn parse_input(input: &str, rules: &dyn RuleSet) -> Vec<(Hand, u32)> {
     input
         .lines()
         .map(|line| {
-            let (hand_str, bid) = line.split_at(5);
-            let hand = Hand::from_string(hand_str);
+            let (hand_str, bid) = line.trim().rsplit_once(' ').expect("Missing bid");
+            let hand = Hand::from_string(hand_str.trim(), rules);
 
-            (hand, bid.trim().parse::<u32>().unwrap())
+            (hand, bid.parse::<u32>().unwrap())
         })
         .collect()
 }
 
-fn get_bet_total_naive(input: &str) -> u32 {
-    let mut bets = parse_input(input);
+fn get_bet_total_naive(input: &str, rules: &dyn RuleSet) -> u32 {
+    let mut bets = parse_input(input, rules);
 
     bets.sort_by(|(hand_a, _), (hand_b, _)| hand_a.cmp(hand_b));
     bets.iter()
@@ -36,6 +108,7 @@ fn get_bet_total_naive(input: &str) -> u32 {
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
 enum Card {
+    Joker,
     Unit(u8),
     Ten,
     Jack,
@@ -47,6 +120,7 @@ enum Card {
 impl Display for Card {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match self {
+            Card::Joker => write!(f, "J"),
             Card::Unit(n) => write!(f, "{}", n),
             Card::Ten => write!(f, "T"),
             Card::Jack => write!(f, "J"),
@@ -58,22 +132,40 @@ impl Display for Card {
 }
 
 impl Card {
-    fn from_char(card: char) -> Card {
+    fn try_from_char(card: char) -> Option<Card> {
         match card {
-            'A' => Card::Ace,
-            'K' => Card::King,
-            'Q' => Card::Queen,
-            'J' => Card::Jack,
-            'T' => Card::Ten,
-            x => Card::Unit(x.to_digit(10).unwrap() as u8),
+            'A' => Some(Card::Ace),
+            'K' => Some(Card::King),
+            'Q' => Some(Card::Queen),
+            'J' => Some(Card::Jack),
+            'T' => Some(Card::Ten),
+            '2'..='9' => Some(Card::Unit(card.to_digit(10)? as u8)),
+            _ => None,
+        }
+    }
+
+    /// The face value of the card, jokers are worth one and aces fourteen
+    fn value(&self) -> u8 {
+        match self {
+            Card::Joker => 1,
+            Card::Unit(n) => *n,
+            Card::Ten => 10,
+            Card::Jack => 11,
+            Card::Queen => 12,
+            Card::King => 13,
+            Card::Ace => 14,
         }
     }
 }
 
+/// A hand is ordered by its type, then by the rank of each card under the rule set
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 struct Hand {
     hand_type: HandType,
+    ranks: Vec<u8>,
     cards: Vec<Card>,
+    /// Empty if the rule set does not use suits
+    suits: Vec<Suit>,
 }
 
 impl Display for Hand {
@@ -83,49 +175,27 @@ impl Display for Hand {
 }
 
 impl Hand {
-
-    fn new(hand_type: HandType, cards: Vec<Card>) -> Hand {
-        Hand { hand_type, cards }
+    fn from_string(hand: &str, rules: &dyn RuleSet) -> Hand {
+        let (cards, suits) = rules
+            .parse_hand(hand)
+            .unwrap_or_else(|| panic!("Invalid hand for {}: {}", rules.name(), hand));
+        Hand::from_cards(cards, suits, rules)
     }
 
-    fn from_string(hand: &str) -> Hand {
-        let cards = hand.chars().map(Card::from_char).collect::<Vec<_>>();
-        Hand::from_cards(cards)
-    }
-
-    fn from_cards(cards: Vec<Card>) -> Hand {
-        let counter = count_cards(&cards);
-        let num_unique_cards = counter.len();
-
-        if num_unique_cards == 1 {
-            // If there is only one unique card, then it must be a five of a kind
-            Hand::new(HandType::FiveOfAKind, cards)
-        } else if num_unique_cards == 2 {
-            if contains_counts(4, &counter) {
-                // If there are two unique cards, and one of them has a count of 4, then it
-                // must be a four of a kind
-                Hand::new(HandType::FourOfAKind, cards)
-            } else {
-                // Otherwise, it must be a full house
-                Hand::new(HandType::FullHouse, cards)
-            }
-        } else if num_unique_cards == 3 {
-            if contains_counts(3, &counter) {
-                // If there are three unique cards, and one of them has a count of 3, then it
-                // must be a three of a kind
-                Hand::new(HandType::ThreeOfAKind, cards)
-            } else {
-                // Otherwise, it must be a two pair
-                Hand::new(HandType::TwoPair, cards)
-            }
-        } else if num_unique_cards == 4 {
-            // If there are four unique cards, then it must be a one pair
-            Hand::new(HandType::OnePair, cards)
-        } else if num_unique_cards == 5 {
-            // If there are five unique cards, then it must be a high card
-            Hand::new(HandType::HighCard, cards)
-        } else {
-            panic!("Invalid number of unique cards: {}", num_unique_cards);
+    fn from_cards(cards: Vec<Card>, suits: Vec<Suit>, rules: &dyn RuleSet) -> Hand {
+        assert_eq!(
+            cards.len(),
+            rules.hand_size(),
+            "{} hands have {} cards",
+            rules.name(),
+            rules.hand_size()
+        );
+
+        Hand {
+            hand_type: rules.hand_type(&cards, &suits),
+            ranks: rules.tiebreak_ranks(&cards),
+            cards,
+            suits,
         }
     }
 }
@@ -136,18 +206,14 @@ enum HandType {
     OnePair,
     TwoPair,
     ThreeOfAKind,
+    Straight,
+    Flush,
     FullHouse,
     FourOfAKind,
+    StraightFlush,
     FiveOfAKind,
 }
 
-/// Returns true if the counter contains a card with the given count
-fn contains_counts(count: u32, counter: &BTreeMap<Card, u32>) -> bool {
-    counter
-        .iter()
-        .any(|(_, &card_count)| card_count == count)
-}
-
 fn count_cards(cards: &[Card]) -> BTreeMap<Card, u32> {
     cards.iter().fold(BTreeMap::new(), |mut acc, card| {
         *acc.entry(*card).or_insert(0) += 1;
@@ -160,6 +226,12 @@ mod test {
     use super::*;
     use indoc::indoc;
 
+    impl Card {
+        fn from_char(card: char) -> Card {
+            Card::try_from_char(card).unwrap_or_else(|| panic!("Invalid card: {}", card))
+        }
+    }
+
     #[test]
     fn card_from_string() {
         let sample_input = "32T3K";
@@ -273,14 +345,14 @@ mod test {
     fn test_parse_hands() {
         let sample_input = "32T3K";
 
-        let hand = Hand::from_string(sample_input);
+        let hand = Hand::from_string(sample_input, &CamelCards);
         assert_eq!(hand.hand_type, HandType::OnePair);
     }
 
     #[test]
     fn test_parse_hands_2() {
         let sample_input = "T55J5";
-        let hand = Hand::from_string(sample_input);
+        let hand = Hand::from_string(sample_input, &CamelCards);
 
         assert_eq!(hand.hand_type, HandType::ThreeOfAKind);
     }
@@ -288,7 +360,7 @@ mod test {
     #[test]
     fn test_parse_hands_3() {
         let sample_input = "776KK";
-        let hand = Hand::from_string(sample_input);
+        let hand = Hand::from_string(sample_input, &CamelCards);
 
         assert_eq!(hand.hand_type, HandType::TwoPair)
     }
@@ -296,7 +368,7 @@ mod test {
     #[test]
     fn test_parse_hands_4() {
         let sample_input = "KTJJT";
-        let hand = Hand::from_string(sample_input);
+        let hand = Hand::from_string(sample_input, &CamelCards);
 
         assert_eq!(hand.hand_type, HandType::TwoPair);
     }
@@ -304,7 +376,7 @@ mod test {
     #[test]
     fn test_parse_hands_5() {
         let sample_input = "QQQJA";
-        let hand = Hand::from_string(sample_input);
+        let hand = Hand::from_string(sample_input, &CamelCards);
         assert_eq!(hand.hand_type, HandType::ThreeOfAKind);
     }
 
@@ -318,7 +390,7 @@ mod test {
             QQQJA 483
         "};
 
-        let bets = parse_input(sample_input);
+        let bets = parse_input(sample_input, &CamelCards);
         assert_eq!(bets.len(), 5);
     }
 
@@ -332,9 +404,61 @@ mod test {
             QQQJA 483
         "};
 
-        let total = get_bet_total_naive(sample_input);
+        let total = get_bet_total_naive(sample_input, &CamelCards);
         let total_expected = 6440;
 
         assert_eq!(total_expected, total);
     }
+
+    #[test]
+    fn get_bet_total_jokers_test() {
+        let sample_input = indoc! {"
+            32T3K 765
+            T55J5 684
+            KK677 28
+            KTJJT 220
+            QQQJA 483
+        "};
+
+        let total = get_bet_total_naive(sample_input, &CamelCardsJokers);
+        let total_expected = 5905;
+
+        assert_eq!(total_expected, total);
+    }
+
+    #[test]
+    fn test_joker_hand_types() {
+        let cases = [
+            ("32T3K", HandType::OnePair),
+            ("T55J5", HandType::FourOfAKind),
+            ("KK677", HandType::TwoPair),
+            ("KTJJT", HandType::FourOfAKind),
+            ("QQQJA", HandType::FourOfAKind),
+            ("JJJJJ", HandType::FiveOfAKind),
+            ("JJJJ2", HandType::FiveOfAKind),
+            ("2345J", HandType::OnePair),
+            ("2233J", HandType::FullHouse),
+            ("J2J3J", HandType::FourOfAKind),
+        ];
+
+        for (input, expected) in cases {
+            let hand = Hand::from_string(input, &CamelCardsJokers);
+            assert_eq!(hand.hand_type, expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_joker_ordering() {
+        assert!(Card::Joker < Card::Unit(2));
+
+        // Both are five of a kind, but the jokers are ranked lowest
+        let jokers = Hand::from_string("JJJJJ", &CamelCardsJokers);
+        let twos = Hand::from_string("22222", &CamelCardsJokers);
+        assert!(jokers < twos);
+
+        let jokers = Hand::from_string("KTJJT", &CamelCardsJokers);
+        let jacks = Hand::from_string("KTJJT", &CamelCards);
+        assert_eq!(jacks.hand_type, HandType::TwoPair);
+        assert!(jokers > jacks);
+    }
 }
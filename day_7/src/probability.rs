@@ -0,0 +1,243 @@
+use crate::rule_set::{distinct_cards, RuleSet};
+use crate::{Card, Hand, HandType};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// Print the probability table for the rule set, and the expected rank of the hand if
+/// one is given
+pub(crate) fn odds_report(rules: &dyn RuleSet, hand: Option<&str>) {
+    let table = ProbabilityTable::new(rules);
+    println!("{}", rules.name());
+    print!("{}", table);
+
+    if let Some(hand) = hand {
+        let hand = Hand::from_string(hand, rules);
+        let ranking = HandRanking::new(rules);
+        let rank = ranking.expected_rank(&hand);
+        println!(
+            "{} ranks {:.1} of {} ({:.3}% of hands are weaker)",
+            hand,
+            rank,
+            ranking.len(),
+            100.0 * ranking.weaker_fraction(&hand)
+        );
+    }
+}
+
+/// Exact counts of each hand type when every card is equally likely
+#[derive(Debug, PartialEq)]
+pub(crate) struct ProbabilityTable {
+    /// The number of distinct multisets of cards with each hand type
+    pub(crate) multisets: BTreeMap<HandType, u64>,
+    /// The number of ordered hands with each hand type
+    pub(crate) hands: BTreeMap<HandType, u64>,
+    pub(crate) total_hands: u64,
+}
+
+impl ProbabilityTable {
+    /// Enumerate every multiset of cards and weight it by the number of ways it can be
+    /// ordered, which is far fewer hands to classify than every ordered hand.
+    pub(crate) fn new(rules: &dyn RuleSet) -> ProbabilityTable {
+        let cards = enumerable_cards(rules);
+
+        let mut multisets = BTreeMap::new();
+        let mut hands = BTreeMap::new();
+        for_each_multiset(&cards, rules.hand_size(), &mut |multiset| {
+            let hand_type = rules.hand_type(multiset, &[]);
+            *multisets.entry(hand_type).or_insert(0) += 1;
+            *hands.entry(hand_type).or_insert(0) += n_orderings(multiset);
+        });
+
+        ProbabilityTable {
+            multisets,
+            hands,
+            total_hands: (cards.len() as u64).pow(rules.hand_size() as u32),
+        }
+    }
+
+    pub(crate) fn probability(&self, hand_type: HandType) -> f64 {
+        self.hands.get(&hand_type).copied().unwrap_or(0) as f64 / self.total_hands as f64
+    }
+}
+
+impl Display for ProbabilityTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<14} {:>9} {:>9} {:>12}",
+            "Type", "Multisets", "Hands", "Probability"
+        )?;
+        for (hand_type, hands) in self.hands.iter() {
+            let name = format!("{:?}", hand_type);
+            writeln!(
+                f,
+                "{:<14} {:>9} {:>9} {:>12.8}",
+                name,
+                self.multisets[hand_type],
+                hands,
+                self.probability(*hand_type)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn enumerable_cards(rules: &dyn RuleSet) -> Vec<Card> {
+    distinct_cards(rules)
+        .unwrap_or_else(|| panic!("{} hands cannot be enumerated without suits", rules.name()))
+}
+
+/// Calls `f` with every sorted multiset of `size` cards, drawn with replacement
+fn for_each_multiset(cards: &[Card], size: usize, f: &mut impl FnMut(&[Card])) {
+    fn recurse(cards: &[Card], size: usize, current: &mut Vec<Card>, f: &mut impl FnMut(&[Card])) {
+        if current.len() == size {
+            f(current);
+            return;
+        }
+        for (idx, &card) in cards.iter().enumerate() {
+            current.push(card);
+            // Only allow the same or later cards, so each multiset is visited once
+            recurse(&cards[idx..], size, current, f);
+            current.pop();
+        }
+    }
+
+    recurse(cards, size, &mut Vec::with_capacity(size), f);
+}
+
+/// The number of distinct orderings of a sorted multiset, `n! / (k1! * k2! * ...)`
+fn n_orderings(multiset: &[Card]) -> u64 {
+    let factorial = |n: usize| (1..=n as u64).product::<u64>();
+    let repeats = multiset
+        .chunk_by(|a, b| a == b)
+        .map(|group| factorial(group.len()))
+        .product::<u64>();
+    factorial(multiset.len()) / repeats
+}
+
+/// The packed sort key of every possible ordered hand, used to find where a hand ranks
+pub(crate) struct HandRanking {
+    keys: Vec<u64>,
+}
+
+impl HandRanking {
+    pub(crate) fn new(rules: &dyn RuleSet) -> HandRanking {
+        let cards = enumerable_cards(rules);
+        let size = rules.hand_size();
+
+        // Count through every hand like an odometer, with one digit per card
+        let mut digits = vec![0; size];
+        let mut keys = Vec::with_capacity(cards.len().pow(size as u32));
+        loop {
+            let hand_cards = digits.iter().map(|&digit| cards[digit]).collect();
+            keys.push(Hand::from_cards(hand_cards, vec![], rules).sort_key());
+
+            let Some(pos) = digits.iter().rposition(|&digit| digit + 1 < cards.len()) else {
+                break;
+            };
+            digits[pos] += 1;
+            digits[pos + 1..].fill(0);
+        }
+
+        keys.sort_unstable();
+        HandRanking { keys }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// The rank the hand would get if every possible hand was played, one being the
+    /// weakest. Hands that compare equal share the average of their ranks.
+    pub(crate) fn expected_rank(&self, hand: &Hand) -> f64 {
+        let key = hand.sort_key();
+        let weaker = self.keys.partition_point(|&k| k < key);
+        let equal = self.keys[weaker..].partition_point(|&k| k == key);
+        weaker as f64 + (equal as f64 + 1.0) / 2.0
+    }
+
+    /// The fraction of all possible hands that are strictly weaker than the hand
+    pub(crate) fn weaker_fraction(&self, hand: &Hand) -> f64 {
+        let key = hand.sort_key();
+        self.keys.partition_point(|&k| k < key) as f64 / self.keys.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rule_set::{CamelCards, CamelCardsJokers};
+
+    #[test]
+    fn test_camel_card_counts() {
+        let table = ProbabilityTable::new(&CamelCards);
+        assert_eq!(table.total_hands, 371_293);
+        assert_eq!(table.multisets.values().sum::<u64>(), 6188);
+        assert_eq!(table.hands.values().sum::<u64>(), table.total_hands);
+
+        let expected = BTreeMap::from([
+            (HandType::HighCard, 154_440),
+            (HandType::OnePair, 171_600),
+            (HandType::TwoPair, 25_740),
+            (HandType::ThreeOfAKind, 17_160),
+            (HandType::FullHouse, 1_560),
+            (HandType::FourOfAKind, 780),
+            (HandType::FiveOfAKind, 13),
+        ]);
+        assert_eq!(table.hands, expected);
+        assert_eq!(table.multisets[&HandType::FiveOfAKind], 13);
+        assert_eq!(table.probability(HandType::Straight), 0.0);
+    }
+
+    #[test]
+    fn test_joker_counts() {
+        let plain = ProbabilityTable::new(&CamelCards);
+        let jokers = ProbabilityTable::new(&CamelCardsJokers);
+        assert_eq!(jokers.hands.values().sum::<u64>(), jokers.total_hands);
+
+        // Jokers can only improve a hand
+        assert!(jokers.hands[&HandType::HighCard] < plain.hands[&HandType::HighCard]);
+        assert!(jokers.hands[&HandType::FiveOfAKind] > plain.hands[&HandType::FiveOfAKind]);
+
+        // A high card hand cannot contain a joker
+        assert_eq!(jokers.hands[&HandType::HighCard], 12 * 11 * 10 * 9 * 8);
+    }
+
+    #[test]
+    fn test_n_orderings() {
+        let cards = [
+            Card::Unit(2),
+            Card::Unit(2),
+            Card::Unit(3),
+            Card::Ace,
+            Card::Ace,
+        ];
+        assert_eq!(n_orderings(&cards), 30);
+        assert_eq!(n_orderings(&[Card::Ace; 5]), 1);
+    }
+
+    #[test]
+    fn test_expected_rank() {
+        let ranking = HandRanking::new(&CamelCards);
+        assert_eq!(ranking.len(), 371_293);
+
+        let weakest = Hand::from_string("23456", &CamelCards);
+        let strongest = Hand::from_string("AAAAA", &CamelCards);
+        assert_eq!(ranking.expected_rank(&weakest), 1.0);
+        assert_eq!(ranking.expected_rank(&strongest), 371_293.0);
+        assert_eq!(ranking.weaker_fraction(&weakest), 0.0);
+
+        // Every five of a kind beats every other hand type
+        let twos = Hand::from_string("22222", &CamelCards);
+        assert_eq!(ranking.expected_rank(&twos), (371_293 - 12) as f64);
+
+        let jokers = HandRanking::new(&CamelCardsJokers);
+        let all_jokers = Hand::from_string("JJJJJ", &CamelCardsJokers);
+        let n_five_of_a_kind =
+            ProbabilityTable::new(&CamelCardsJokers).hands[&HandType::FiveOfAKind] as f64;
+        assert_eq!(
+            jokers.expected_rank(&all_jokers),
+            371_293.0 - n_five_of_a_kind + 1.0
+        );
+    }
+}
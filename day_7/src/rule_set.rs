@@ -0,0 +1,295 @@
+use crate::{count_cards, Card, HandType};
+
+/// Returns the rule set with the given name, one of `camel`, `jokers` or `poker`
+pub(crate) fn from_name(name: &str) -> Option<Box<dyn RuleSet>> {
+    match name {
+        "camel" => Some(Box::new(CamelCards)),
+        "jokers" => Some(Box::new(CamelCardsJokers)),
+        "poker" => Some(Box::new(Poker)),
+        _ => None,
+    }
+}
+
+/// Returns one of each card as read by the rule set, or `None` if the cards need suits
+pub(crate) fn distinct_cards(rules: &dyn RuleSet) -> Option<Vec<Card>> {
+    rules.parse_hand("23456789TJQKA").map(|(cards, _)| cards)
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub(crate) enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl Suit {
+    fn from_char(suit: char) -> Option<Suit> {
+        match suit.to_ascii_uppercase() {
+            'C' => Some(Suit::Clubs),
+            'D' => Some(Suit::Diamonds),
+            'H' => Some(Suit::Hearts),
+            'S' => Some(Suit::Spades),
+            _ => None,
+        }
+    }
+}
+
+/// Controls how hands are read, classified and ordered.
+///
+/// The default methods give Camel Cards without jokers.
+pub(crate) trait RuleSet {
+    fn name(&self) -> &'static str;
+
+    fn hand_size(&self) -> usize {
+        5
+    }
+
+    /// Parse the cards of a hand, along with their suits if the rule set uses them
+    fn parse_hand(&self, hand: &str) -> Option<(Vec<Card>, Vec<Suit>)> {
+        let cards = hand
+            .chars()
+            .map(Card::try_from_char)
+            .collect::<Option<Vec<_>>>()?;
+        Some((cards, Vec::new()))
+    }
+
+    /// The rank of a card when comparing hands of the same type, higher is better
+    fn card_rank(&self, card: Card) -> u8 {
+        card.value()
+    }
+
+    /// Returns true if the card can stand in for any other card
+    fn is_wildcard(&self, _card: Card) -> bool {
+        false
+    }
+
+    fn hand_type(&self, cards: &[Card], _suits: &[Suit]) -> HandType {
+        group_hand_type(cards, |card| self.is_wildcard(card))
+    }
+
+    /// The ranks used to break ties between hands of the same type, compared in order
+    fn tiebreak_ranks(&self, cards: &[Card]) -> Vec<u8> {
+        cards.iter().map(|&card| self.card_rank(card)).collect()
+    }
+}
+
+/// Camel Cards, where ties are broken by the first card that differs
+pub(crate) struct CamelCards;
+
+impl RuleSet for CamelCards {
+    fn name(&self) -> &'static str {
+        "Camel Cards"
+    }
+}
+
+/// Camel Cards where `J` is a joker, it is ranked lowest but makes the best hand it can
+pub(crate) struct CamelCardsJokers;
+
+impl RuleSet for CamelCardsJokers {
+    fn name(&self) -> &'static str {
+        "Camel Cards with jokers"
+    }
+
+    fn parse_hand(&self, hand: &str) -> Option<(Vec<Card>, Vec<Suit>)> {
+        let (cards, suits) = CamelCards.parse_hand(hand)?;
+        let cards = cards
+            .into_iter()
+            .map(|card| match card {
+                Card::Jack => Card::Joker,
+                card => card,
+            })
+            .collect();
+        Some((cards, suits))
+    }
+
+    fn is_wildcard(&self, card: Card) -> bool {
+        card == Card::Joker
+    }
+}
+
+/// Five card poker, each card is written as its rank then suit, e.g. `AhKhQhJhTh`.
+///
+/// Ties are broken by the largest group of cards first, then by rank, rather than by
+/// the order the cards were dealt.
+pub(crate) struct Poker;
+
+impl RuleSet for Poker {
+    fn name(&self) -> &'static str {
+        "Poker"
+    }
+
+    fn parse_hand(&self, hand: &str) -> Option<(Vec<Card>, Vec<Suit>)> {
+        let chars = hand
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<Vec<_>>();
+        if chars.len() % 2 != 0 {
+            return None;
+        }
+
+        chars
+            .chunks(2)
+            .map(|pair| Some((Card::try_from_char(pair[0])?, Suit::from_char(pair[1])?)))
+            .collect::<Option<Vec<_>>>()
+            .map(|cards| cards.into_iter().unzip())
+    }
+
+    fn hand_type(&self, cards: &[Card], suits: &[Suit]) -> HandType {
+        let groups = group_hand_type(cards, |_| false);
+        let is_flush = suits.windows(2).all(|pair| pair[0] == pair[1]);
+        let is_straight = straight_ranks(cards).is_some();
+
+        match (is_straight, is_flush) {
+            (true, true) => HandType::StraightFlush,
+            (true, false) => groups.max(HandType::Straight),
+            (false, true) => groups.max(HandType::Flush),
+            (false, false) => groups,
+        }
+    }
+
+    fn tiebreak_ranks(&self, cards: &[Card]) -> Vec<u8> {
+        if let Some(ranks) = straight_ranks(cards) {
+            return ranks;
+        }
+
+        let mut groups = count_cards(cards)
+            .into_iter()
+            .map(|(card, count)| (count, self.card_rank(card)))
+            .collect::<Vec<_>>();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+
+        groups
+            .into_iter()
+            .flat_map(|(count, rank)| std::iter::repeat_n(rank, count as usize))
+            .collect()
+    }
+}
+
+/// Returns the ranks of the straight from highest to lowest, or `None` if the cards are
+/// not a straight. An ace can be used below a two.
+fn straight_ranks(cards: &[Card]) -> Option<Vec<u8>> {
+    let mut ranks = cards.iter().map(|card| card.value()).collect::<Vec<_>>();
+    ranks.sort_unstable_by(|a, b| b.cmp(a));
+
+    if ranks.len() < 2 {
+        return None;
+    }
+    if ranks == [14, 5, 4, 3, 2] {
+        return Some(vec![5, 4, 3, 2, 1]);
+    }
+    ranks
+        .windows(2)
+        .all(|pair| pair[0] == pair[1] + 1)
+        .then_some(ranks)
+}
+
+/// Classify the hand by the size of its groups of matching cards.
+///
+/// Adding the wildcards to the most common card always gives the best hand type, as it
+/// either increases the largest group or turns two pair into a full house.
+pub(crate) fn group_hand_type(cards: &[Card], is_wildcard: impl Fn(Card) -> bool) -> HandType {
+    let n_wildcards = cards.iter().filter(|&&card| is_wildcard(card)).count() as u32;
+    let natural = cards
+        .iter()
+        .copied()
+        .filter(|&card| !is_wildcard(card))
+        .collect::<Vec<_>>();
+
+    let mut counts = count_cards(&natural).into_values().collect::<Vec<_>>();
+    counts.sort_unstable_by(|a, b| b.cmp(a));
+    match counts.first_mut() {
+        Some(largest) => *largest += n_wildcards,
+        None => counts.push(n_wildcards),
+    }
+
+    match (counts[0], counts.get(1).copied().unwrap_or(0)) {
+        (5.., _) => HandType::FiveOfAKind,
+        (4, _) => HandType::FourOfAKind,
+        (3, 2..) => HandType::FullHouse,
+        (3, _) => HandType::ThreeOfAKind,
+        (2, 2..) => HandType::TwoPair,
+        (2, _) => HandType::OnePair,
+        _ => HandType::HighCard,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{get_bet_total_naive, Hand};
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_poker_hand() {
+        let (cards, suits) = Poker.parse_hand("AhTs2c").unwrap();
+        assert_eq!(cards, vec![Card::Ace, Card::Ten, Card::Unit(2)]);
+        assert_eq!(suits, vec![Suit::Hearts, Suit::Spades, Suit::Clubs]);
+
+        assert_eq!(Poker.parse_hand("AhT"), None);
+        assert_eq!(Poker.parse_hand("AhTx"), None);
+        assert_eq!(CamelCards.parse_hand("AK1QJ"), None);
+    }
+
+    #[test]
+    fn test_poker_hand_types() {
+        let cases = [
+            ("AhKhQhJhTh", HandType::StraightFlush),
+            ("5d4c3h2sAs", HandType::Straight),
+            ("9hKh2h5h7h", HandType::Flush),
+            ("9h9d9s5h5c", HandType::FullHouse),
+            ("9h9d9s9c5c", HandType::FourOfAKind),
+            ("QhKdAs2c3c", HandType::HighCard),
+            ("2h2d3s3cAc", HandType::TwoPair),
+        ];
+
+        for (input, expected) in cases {
+            let hand = Hand::from_string(input, &Poker);
+            assert_eq!(hand.hand_type, expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_poker_tiebreaks() {
+        // The pair is compared before the kicker, regardless of the order of the cards
+        let kings = Hand::from_string("2hKd3sKc4c", &Poker);
+        let queens = Hand::from_string("AhQdKsQc4c", &Poker);
+        assert!(kings > queens);
+
+        // An ace low straight is the lowest straight
+        let wheel = Hand::from_string("Ah2d3s4c5c", &Poker);
+        let six_high = Hand::from_string("6h2d3s4c5c", &Poker);
+        assert!(wheel < six_high);
+
+        // Under Camel Cards only the first card matters
+        let kings = Hand::from_string("2KQK4", &CamelCards);
+        let queens = Hand::from_string("AQKQ4", &CamelCards);
+        assert!(kings < queens);
+    }
+
+    #[test]
+    fn test_group_hand_type_sizes() {
+        // Larger hands are classified by their largest groups
+        let cards = [Card::Ace; 6];
+        assert_eq!(group_hand_type(&cards, |_| false), HandType::FiveOfAKind);
+
+        let cards = [Card::Joker, Card::Joker, Card::Ten];
+        assert_eq!(
+            group_hand_type(&cards, |c| c == Card::Joker),
+            HandType::ThreeOfAKind
+        );
+    }
+
+    #[test]
+    fn test_poker_bet_total() {
+        let sample_input = indoc! {"
+            AhKhQhJhTh 10
+            2h2d3s3cAc 20
+            QhKdAs2c3c 30
+        "};
+
+        // Ranked high card, two pair, then straight flush
+        let expected = 30 + 20 * 2 + 10 * 3;
+        assert_eq!(get_bet_total_naive(sample_input, &Poker), expected);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::Hand;
+
+/// Bits used for each card rank in a packed key
+const RANK_BITS: u32 = 4;
+/// The hand type is stored in the bits above the ranks
+const TYPE_SHIFT: u32 = u64::BITS - RANK_BITS;
+
+impl Hand {
+    /// Pack the hand type and tiebreak ranks into a single integer.
+    ///
+    /// The hand type takes the top four bits and the tiebreak ranks follow in order,
+    /// four bits each, so hands of up to fifteen cards can be packed. Under Camel Cards
+    /// the key sorts in the same order as the hand, but Poker hands that only differ by
+    /// suit or by the order of the cards have the same key, where the hand would still
+    /// break the tie on the cards as dealt.
+    pub(crate) fn sort_key(&self) -> u64 {
+        assert!(
+            self.ranks.len() as u32 * RANK_BITS <= TYPE_SHIFT,
+            "Too many cards to pack into a key"
+        );
+
+        let mut key = (self.hand_type as u64) << TYPE_SHIFT;
+        for (idx, &rank) in self.ranks.iter().enumerate() {
+            assert!(rank < 1 << RANK_BITS, "Rank {} does not fit in a key", rank);
+            key |= (rank as u64) << (TYPE_SHIFT - RANK_BITS * (idx as u32 + 1));
+        }
+        key
+    }
+}
+
+/// Stable least significant digit radix sort on the key, one byte at a time.
+///
+/// Any byte that is the same for every key is skipped, which is most of the low bytes
+/// for five card hands.
+pub(crate) fn radix_sort<T: Copy>(items: &mut Vec<(u64, T)>) {
+    if items.len() < 2 {
+        return;
+    }
+    let mut buffer = items.clone();
+
+    for shift in (0..u64::BITS).step_by(8) {
+        let mut counts = [0usize; 256];
+        for (key, _) in items.iter() {
+            counts[((key >> shift) & 0xff) as usize] += 1;
+        }
+        if counts.contains(&items.len()) {
+            continue;
+        }
+
+        // Turn the counts into the starting offset of each digit
+        let mut offset = 0;
+        for count in counts.iter_mut() {
+            let n = *count;
+            *count = offset;
+            offset += n;
+        }
+
+        for item in items.iter() {
+            let digit = ((item.0 >> shift) & 0xff) as usize;
+            buffer[counts[digit]] = *item;
+            counts[digit] += 1;
+        }
+        std::mem::swap(items, &mut buffer);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rule_set::{CamelCards, CamelCardsJokers, Poker, RuleSet};
+    use crate::{get_bet_total_naive, parse_input, HandType};
+    use indoc::indoc;
+    use std::cmp::Ordering;
+
+    /// Same as `get_bet_total_naive`, but the hands are sorted as packed keys
+    fn get_bet_total_radix(input: &str, rules: &dyn RuleSet) -> u64 {
+        let mut bets = parse_input(input, rules)
+            .into_iter()
+            .map(|(hand, bid)| (hand.sort_key(), bid))
+            .collect::<Vec<_>>();
+
+        radix_sort(&mut bets);
+        bets.iter()
+            .enumerate()
+            .map(|(pos, (_, bid))| (pos as u64 + 1) * *bid as u64)
+            .sum()
+    }
+
+    const SAMPLE: &str = indoc! {"
+        32T3K 765
+        T55J5 684
+        KK677 28
+        KTJJT 220
+        QQQJA 483
+    "};
+
+    #[test]
+    fn test_sort_key_layout() {
+        let hand = Hand::from_string("32T3K", &CamelCards);
+        let expected = (HandType::OnePair as u64) << 60 | 0x32A3D << 40;
+        assert_eq!(hand.sort_key(), expected);
+    }
+
+    #[test]
+    fn test_sort_key_order() {
+        let hands = [
+            "32T3K", "T55J5", "KK677", "KTJJT", "QQQJA", "JJJJJ", "22222",
+        ];
+        for rules in [&CamelCards as &dyn RuleSet, &CamelCardsJokers] {
+            for a in hands.iter() {
+                for b in hands.iter() {
+                    let (a, b) = (Hand::from_string(a, rules), Hand::from_string(b, rules));
+                    assert_eq!(a.cmp(&b), a.sort_key().cmp(&b.sort_key()));
+                }
+            }
+        }
+
+        // The key ignores the order the cards were dealt in, the hand does not
+        let a = Hand::from_string("KhKd2s3c4c", &Poker);
+        let b = Hand::from_string("2sKh3cKd4c", &Poker);
+        assert_eq!(a.sort_key(), b.sort_key());
+        assert_ne!(a.cmp(&b), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_radix_sort() {
+        let mut items = vec![(0xff00, 'a'), (3, 'b'), (u64::MAX, 'c'), (3, 'd'), (0, 'e')];
+        radix_sort(&mut items);
+        let expected = vec![(0, 'e'), (3, 'b'), (3, 'd'), (0xff00, 'a'), (u64::MAX, 'c')];
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn test_radix_bet_total() {
+        assert_eq!(get_bet_total_radix(SAMPLE, &CamelCards), 6440);
+        assert_eq!(get_bet_total_radix(SAMPLE, &CamelCardsJokers), 5905);
+
+        let input = include_str!("problem_text");
+        assert_eq!(
+            get_bet_total_radix(input, &CamelCards),
+            get_bet_total_naive(input, &CamelCards) as u64
+        );
+    }
+}
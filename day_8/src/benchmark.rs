@@ -0,0 +1,106 @@
+use crate::network::Network;
+use crate::{parse_instructions, parse_network, Element};
+use std::fmt::Write;
+use std::time::Instant;
+use xorshift::XorShift;
+
+/// Compare walking a generated network through the interned `Network` against the
+/// original `HashMap` of heap allocated names.
+pub(crate) fn benchmark(n_nodes: usize, n_steps: usize) {
+    let mut rng = XorShift::new(0x5eed);
+    let input = generate_network(n_nodes, 300, &mut rng);
+
+    let start = Instant::now();
+    let (rest, instructions) = parse_instructions(&input).expect("Parse Error");
+    let (_, map) = parse_network(rest.trim_start()).expect("Parse Error");
+    let map_parse = start.elapsed();
+
+    let start = Instant::now();
+    let mut node = Element::new("0A");
+    for &instruction in instructions.iter().cycle().take(n_steps) {
+        node = map[&node].follow(instruction).clone();
+    }
+    let map_walk = start.elapsed();
+
+    let start = Instant::now();
+    let network = Network::parse(&input).expect("Generated networks are valid");
+    let interned_parse = start.elapsed();
+
+    let start = Instant::now();
+    let mut id = network.id("0A").expect("Node 0A is always generated");
+    for &instruction in network.instructions.iter().cycle().take(n_steps) {
+        id = network.next(id, instruction);
+    }
+    let interned_walk = start.elapsed();
+
+    assert_eq!(network.name(id), &node, "Representations disagree");
+
+    println!("Nodes: {}, steps: {}", network.len(), n_steps);
+    println!(
+        "HashMap:  parse {:>10.2?}, walk {:>10.2?}",
+        map_parse, map_walk
+    );
+    println!(
+        "Interned: parse {:>10.2?}, walk {:>10.2?}",
+        interned_parse, interned_walk
+    );
+}
+
+/// Generate a network in the puzzle format with random edges. Node names are their index
+/// followed by `A` for the first few, `Z` for the last few, and `X` otherwise.
+fn generate_network(n_nodes: usize, n_instructions: usize, rng: &mut XorShift) -> String {
+    let n_ends = n_nodes.div_ceil(100).min(6);
+    let name = |idx: usize| {
+        let suffix = if idx < n_ends {
+            'A'
+        } else if idx >= n_nodes - n_ends {
+            'Z'
+        } else {
+            'X'
+        };
+        format!("{}{}", idx, suffix)
+    };
+
+    let mut input = (0..n_instructions)
+        .map(|_| {
+            if rng.next_u64().is_multiple_of(2) {
+                'L'
+            } else {
+                'R'
+            }
+        })
+        .collect::<String>();
+    input.push_str("\n\n");
+
+    for idx in 0..n_nodes {
+        let left = (rng.next_u64() % n_nodes as u64) as usize;
+        let right = (rng.next_u64() % n_nodes as u64) as usize;
+        writeln!(input, "{} = ({}, {})", name(idx), name(left), name(right))
+            .expect("Writing to a String cannot fail");
+    }
+    input
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_representations_agree() {
+        let mut rng = XorShift::new(42);
+        let input = generate_network(1000, 50, &mut rng);
+
+        let (rest, instructions) = parse_instructions(&input).unwrap();
+        let (_, map) = parse_network(rest.trim_start()).unwrap();
+        let network = Network::parse(&input).unwrap();
+        assert_eq!(network.len(), map.len());
+
+        let mut node = Element::new("0A");
+        let mut id = network.id("0A").unwrap();
+        for &instruction in instructions.iter().cycle().take(10_000) {
+            node = map[&node].follow(instruction).clone();
+            id = network.next(id, instruction);
+            assert_eq!(network.name(id), &node);
+        }
+    }
+}
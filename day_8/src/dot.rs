@@ -0,0 +1,235 @@
+use crate::network::{Network, NodeId};
+use crate::Instruction;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// How an edge was used by the overlaid walk
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum EdgeUse {
+    /// Taken on the way into the loop
+    Path,
+    /// Part of the loop the walk repeats forever
+    Cycle,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct DotOptions {
+    /// Overlay the walk from this node, following the instructions
+    pub(crate) walk_from: Option<NodeId>,
+    /// Only include the nodes that can be reached from this node along any edges
+    pub(crate) reachable_from: Option<NodeId>,
+}
+
+/// Write the network as a GraphViz digraph, with starts in green and goals in red.
+///
+/// Each edge is labelled with the instruction that follows it, and an overlaid walk is
+/// drawn in blue until it enters its loop, and in orange around the loop.
+pub(crate) fn to_dot(network: &Network, options: &DotOptions) -> String {
+    let included = match options.reachable_from {
+        Some(start) => reachable_nodes(network, start),
+        None => (0..network.len() as NodeId).collect(),
+    };
+    let walked = options
+        .walk_from
+        .map(|start| walk_edges(network, start))
+        .unwrap_or_default();
+
+    let mut dot = String::from("digraph network {\n    node [shape=circle];\n");
+    let mut nodes = included.iter().copied().collect::<Vec<_>>();
+    nodes.sort_unstable();
+
+    for &node in nodes.iter() {
+        let colour = match (network.is_start(node), network.is_goal(node)) {
+            (true, true) => Some("gold"),
+            (true, false) => Some("palegreen"),
+            (false, true) => Some("lightcoral"),
+            (false, false) => None,
+        };
+        let mut attributes = Vec::new();
+        if let Some(colour) = colour {
+            attributes.push(format!("style=filled, fillcolor={}", colour));
+        }
+        if options.walk_from == Some(node) {
+            attributes.push("penwidth=3".to_string());
+        }
+
+        let name = &network.name(node).0;
+        if attributes.is_empty() {
+            writeln!(dot, "    \"{}\";", name).unwrap();
+        } else {
+            writeln!(dot, "    \"{}\" [{}];", name, attributes.join(", ")).unwrap();
+        }
+    }
+
+    for &node in nodes.iter() {
+        let left = network.next(node, Instruction::L);
+        let right = network.next(node, Instruction::R);
+
+        // Both instructions lead to the same node, so draw a single edge
+        let edges = if left == right {
+            vec![(left, "L/R", [Instruction::L, Instruction::R].as_slice())]
+        } else {
+            vec![
+                (left, "L", [Instruction::L].as_slice()),
+                (right, "R", [Instruction::R].as_slice()),
+            ]
+        };
+
+        for (target, label, instructions) in edges {
+            let edge_use = instructions
+                .iter()
+                .filter_map(|&instruction| walked.get(&(node, instruction)))
+                .max_by_key(|&&edge_use| edge_use == EdgeUse::Cycle);
+            let style = match edge_use {
+                Some(EdgeUse::Path) => ", color=blue, penwidth=2",
+                Some(EdgeUse::Cycle) => ", color=orange, penwidth=2",
+                None => "",
+            };
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{}\"{}];",
+                network.name(node).0,
+                network.name(target).0,
+                label,
+                style
+            )
+            .unwrap();
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// Every node that can be reached from `start` along left or right edges, including
+/// `start` itself
+fn reachable_nodes(network: &Network, start: NodeId) -> HashSet<NodeId> {
+    let mut seen = HashSet::from([start]);
+    let mut stack = vec![start];
+    while let Some(node) = stack.pop() {
+        for instruction in [Instruction::L, Instruction::R] {
+            let next = network.next(node, instruction);
+            if seen.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    seen
+}
+
+/// The edges taken by a walk from `start`, until it repeats a (node, instruction) state
+fn walk_edges(network: &Network, start: NodeId) -> HashMap<(NodeId, Instruction), EdgeUse> {
+    let n_instructions = network.instructions.len();
+    let mut first_seen = HashMap::new();
+    let mut states = Vec::new();
+
+    let (mut node, mut idx) = (start, 0);
+    let loop_start = loop {
+        if let Some(&step) = first_seen.get(&(node, idx)) {
+            break step;
+        }
+        first_seen.insert((node, idx), states.len());
+        states.push((node, idx));
+
+        node = network.next(node, network.instructions[idx]);
+        idx = (idx + 1) % n_instructions;
+    };
+
+    let mut edges = HashMap::new();
+    for (step, &(node, idx)) in states.iter().enumerate() {
+        let edge_use = if step < loop_start {
+            EdgeUse::Path
+        } else {
+            EdgeUse::Cycle
+        };
+        let entry = edges
+            .entry((node, network.instructions[idx]))
+            .or_insert(edge_use);
+        if edge_use == EdgeUse::Cycle {
+            *entry = EdgeUse::Cycle;
+        }
+    }
+    edges
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    const SAMPLE: &str = indoc! {"
+        LR
+
+        11A = (11B, XXX)
+        11B = (XXX, 11Z)
+        11Z = (11B, XXX)
+        22A = (22B, XXX)
+        XXX = (XXX, XXX)
+        22B = (22Z, 22Z)
+        22Z = (22B, 22B)
+        "};
+
+    #[test]
+    fn test_full_graph() {
+        let network = Network::parse(SAMPLE).unwrap();
+        let dot = to_dot(&network, &DotOptions::default());
+
+        assert!(dot.starts_with("digraph network {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("    \"11A\" [style=filled, fillcolor=palegreen];\n"));
+        assert!(dot.contains("    \"11Z\" [style=filled, fillcolor=lightcoral];\n"));
+        assert!(dot.contains("    \"11B\";\n"));
+        assert!(dot.contains("    \"11A\" -> \"11B\" [label=\"L\"];\n"));
+        assert!(dot.contains("    \"11A\" -> \"XXX\" [label=\"R\"];\n"));
+        assert!(dot.contains("    \"XXX\" -> \"XXX\" [label=\"L/R\"];\n"));
+        assert_eq!(dot.matches("->").count(), 11);
+    }
+
+    #[test]
+    fn test_reachable_subgraph() {
+        let network = Network::parse(SAMPLE).unwrap();
+        let options = DotOptions {
+            walk_from: None,
+            reachable_from: network.id("22B"),
+        };
+        let dot = to_dot(&network, &options);
+
+        let expected = indoc! {r#"
+            digraph network {
+                node [shape=circle];
+                "22B";
+                "22Z" [style=filled, fillcolor=lightcoral];
+                "22B" -> "22Z" [label="L/R"];
+                "22Z" -> "22B" [label="L/R"];
+            }
+            "#};
+        assert_eq!(dot, expected);
+    }
+
+    #[test]
+    fn test_walk_overlay() {
+        let network = Network::parse(SAMPLE).unwrap();
+        let start = network.id("11A").unwrap();
+
+        // 11A -L-> 11B -R-> 11Z -L-> 11B, which repeats the state at step one
+        let edges = walk_edges(&network, start);
+        let id = |name| network.id(name).unwrap();
+        let expected = HashMap::from([
+            ((id("11A"), Instruction::L), EdgeUse::Path),
+            ((id("11B"), Instruction::R), EdgeUse::Cycle),
+            ((id("11Z"), Instruction::L), EdgeUse::Cycle),
+        ]);
+        assert_eq!(edges, expected);
+
+        let options = DotOptions {
+            walk_from: Some(start),
+            reachable_from: Some(start),
+        };
+        let dot = to_dot(&network, &options);
+        assert!(dot.contains("\"11A\" [style=filled, fillcolor=palegreen, penwidth=3];"));
+        assert!(dot.contains("\"11A\" -> \"11B\" [label=\"L\", color=blue, penwidth=2];"));
+        assert!(dot.contains("\"11B\" -> \"11Z\" [label=\"R\", color=orange, penwidth=2];"));
+        assert!(dot.contains("\"11B\" -> \"XXX\" [label=\"L\"];"));
+        assert!(!dot.contains("22A"));
+    }
+}
@@ -0,0 +1,336 @@
+use crate::network::{Network, NetworkError, NodeId};
+use crate::Element;
+use num::integer::Integer;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum GhostError {
+    Network(NetworkError),
+    NoStartingPoints,
+    /// The ghost from this start never stands on a goal node
+    NeverReachesGoal(Element),
+    /// Every ghost reaches a goal, but never all at the same time
+    NoSimultaneousArrival,
+    /// The ghosts do all arrive together, but later than a `u64` step count can hold
+    ArrivalOverflow,
+}
+
+impl Display for GhostError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GhostError::Network(e) => write!(f, "{}", e),
+            GhostError::NoStartingPoints => write!(f, "There are no starting nodes"),
+            GhostError::NeverReachesGoal(start) => {
+                write!(f, "The ghost starting at {} never reaches a goal", start.0)
+            }
+            GhostError::NoSimultaneousArrival => {
+                write!(f, "The ghosts are never all on goal nodes at the same time")
+            }
+            GhostError::ArrivalOverflow => {
+                write!(f, "The ghosts all arrive too late to count the steps")
+            }
+        }
+    }
+}
+
+impl From<NetworkError> for GhostError {
+    fn from(e: NetworkError) -> Self {
+        GhostError::Network(e)
+    }
+}
+
+/// The steps at which a single ghost stands on a goal node.
+///
+/// The state of a ghost is its node and its position in the instructions, so after at
+/// most `nodes * instructions` steps it must repeat a state and loop forever.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct GhostCycle {
+    /// Number of steps before the loop is entered
+    pub(crate) prefix: u64,
+    /// Number of steps to go around the loop once
+    pub(crate) length: u64,
+    /// Goal steps before the loop is entered
+    pub(crate) prefix_hits: Vec<u64>,
+    /// Goal steps during the first time around the loop, each one repeats every `length`
+    pub(crate) cycle_hits: Vec<u64>,
+}
+
+impl GhostCycle {
+    pub(crate) fn find(network: &Network, start: NodeId) -> GhostCycle {
+        let instructions = &network.instructions;
+        let mut seen = HashMap::new();
+        let mut hits = Vec::new();
+        let mut node = start;
+
+        for step in 0u64.. {
+            let idx = (step % instructions.len() as u64) as usize;
+            if let Some(&first) = seen.get(&(node, idx)) {
+                let (prefix_hits, cycle_hits) = hits.iter().partition(|&&hit| hit < first);
+                return GhostCycle {
+                    prefix: first,
+                    length: step - first,
+                    prefix_hits,
+                    cycle_hits,
+                };
+            }
+            seen.insert((node, idx), step);
+
+            if network.is_goal(node) {
+                hits.push(step);
+            }
+            node = network.next(node, instructions[idx]);
+        }
+        unreachable!("The walk always repeats a state")
+    }
+
+    pub(crate) fn is_on_goal(&self, step: u64) -> bool {
+        if step < self.prefix {
+            return self.prefix_hits.contains(&step);
+        }
+        let offset = self.prefix + (step - self.prefix) % self.length;
+        self.cycle_hits.contains(&offset)
+    }
+}
+
+/// Find the first step, after leaving the start, at which every ghost is on a goal.
+///
+/// Steps before every ghost has entered its loop are checked directly. After that each
+/// ghost is on a goal when the step is one of its cycle hits modulo its cycle length,
+/// and these are combined with the Chinese Remainder Theorem.
+pub(crate) fn first_simultaneous_arrival(cycles: &[GhostCycle]) -> Result<u64, GhostError> {
+    let Some(longest) = cycles.iter().max_by_key(|cycle| cycle.prefix) else {
+        return Err(GhostError::NoStartingPoints);
+    };
+
+    // Before the longest prefix is over, the step must be one of its prefix hits
+    let early = longest
+        .prefix_hits
+        .iter()
+        .copied()
+        .filter(|&step| step > 0)
+        .find(|&step| cycles.iter().all(|cycle| cycle.is_on_goal(step)));
+    if let Some(step) = early {
+        return Ok(step);
+    }
+
+    let mut congruences = vec![(0u128, 1u128)];
+    for cycle in cycles {
+        let residues = cycle
+            .cycle_hits
+            .iter()
+            .map(|&hit| (hit as u128 % cycle.length as u128, cycle.length as u128))
+            .collect::<Vec<_>>();
+
+        let mut combined = Vec::new();
+        for &a in congruences.iter() {
+            for &b in residues.iter() {
+                combined.extend(combine(a, b)?);
+            }
+        }
+        congruences = combined;
+    }
+
+    let lower_bound = longest.prefix.max(1) as u128;
+    let mut first = None;
+    for (residue, modulus) in congruences {
+        let step = if residue >= lower_bound {
+            residue
+        } else {
+            (lower_bound - residue)
+                .div_ceil(modulus)
+                .checked_mul(modulus)
+                .and_then(|offset| offset.checked_add(residue))
+                .ok_or(GhostError::ArrivalOverflow)?
+        };
+        first = Some(first.map_or(step, |first: u128| first.min(step)));
+    }
+
+    let first = first.ok_or(GhostError::NoSimultaneousArrival)?;
+    u64::try_from(first).map_err(|_| GhostError::ArrivalOverflow)
+}
+
+/// Combine `x = a (mod m)` and `x = b (mod n)` into a single congruence modulo
+/// `lcm(m, n)`, or `None` if they have no common solution.
+fn combine((a, m): (u128, u128), (b, n): (u128, u128)) -> Result<Option<(u128, u128)>, GhostError> {
+    let (Ok(m_signed), Ok(n_signed)) = (i128::try_from(m), i128::try_from(n)) else {
+        return Err(GhostError::ArrivalOverflow);
+    };
+    let gcd = m_signed.extended_gcd(&n_signed);
+    let g = gcd.gcd;
+
+    let diff = b as i128 - a as i128;
+    if diff % g != 0 {
+        return Ok(None);
+    }
+
+    // m * x = g (mod n), so k = diff / g * x solves a + m * k = b (mod n)
+    let step = n_signed / g;
+    let k = ((diff / g) % step)
+        .checked_mul(gcd.x % step)
+        .ok_or(GhostError::ArrivalOverflow)?
+        .rem_euclid(step);
+    let modulus = (m / g as u128)
+        .checked_mul(n)
+        .ok_or(GhostError::ArrivalOverflow)?;
+    // k is below n / g, so m * k is below the modulus
+    let residue = (a + m * k as u128) % modulus;
+    Ok(Some((residue, modulus)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    fn cycles(input: &str) -> Vec<GhostCycle> {
+        let network = Network::parse(input).unwrap();
+        network
+            .starts()
+            .map(|start| GhostCycle::find(&network, start))
+            .collect()
+    }
+
+    #[test]
+    fn test_combine() {
+        assert_eq!(combine((2, 3), (3, 5)), Ok(Some((8, 15))));
+        assert_eq!(combine((1, 4), (3, 6)), Ok(Some((9, 12))));
+        assert_eq!(combine((0, 4), (1, 6)), Ok(None));
+        assert_eq!(combine((0, 1), (4, 7)), Ok(Some((4, 7))));
+
+        // Coprime moduli whose product is too large for a u128
+        assert_eq!(
+            combine((0, 1 << 100), (1, (1 << 100) + 1)),
+            Err(GhostError::ArrivalOverflow)
+        );
+    }
+
+    #[test]
+    fn test_arrival_overflow() {
+        // Coprime cycle lengths with the hits out of step, so the first arrival is near
+        // the product of the lengths
+        let cycle = |length: u64, hit: u64| GhostCycle {
+            prefix: 0,
+            length,
+            prefix_hits: vec![],
+            cycle_hits: vec![hit],
+        };
+        let two = [cycle(1 << 40, 0), cycle((1 << 40) + 1, 1)];
+        assert_eq!(
+            first_simultaneous_arrival(&two),
+            Err(GhostError::ArrivalOverflow)
+        );
+
+        let three = [
+            cycle(u64::MAX, 1),
+            cycle(u64::MAX - 1, 0),
+            cycle(u64::MAX - 2, 0),
+        ];
+        assert_eq!(
+            first_simultaneous_arrival(&three),
+            Err(GhostError::ArrivalOverflow)
+        );
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let input = indoc! {"
+            LR
+
+            11A = (11B, XXX)
+            11B = (XXX, 11Z)
+            11Z = (11B, XXX)
+            XXX = (XXX, XXX)
+            "};
+
+        let cycles = cycles(input);
+        let expected = GhostCycle {
+            prefix: 1,
+            length: 2,
+            prefix_hits: vec![],
+            cycle_hits: vec![2],
+        };
+        assert_eq!(cycles, vec![expected]);
+        assert!(cycles[0].is_on_goal(4));
+        assert!(!cycles[0].is_on_goal(5));
+    }
+
+    #[test]
+    fn test_offset_cycles() {
+        // The first ghost is on a goal at 2, 5, 8, ... and the second at 3, 7, 11, ...
+        // so plain lcm of the first hits would give 6, which is wrong.
+        let input = indoc! {"
+            L
+
+            11A = (11B, 11B)
+            11B = (11Z, 11Z)
+            11Z = (11C, 11C)
+            11C = (11B, 11B)
+            22A = (22B, 22B)
+            22B = (22C, 22C)
+            22C = (22Z, 22Z)
+            22Z = (22D, 22D)
+            22D = (22B, 22B)
+            "};
+
+        let cycles = cycles(input);
+        assert_eq!(cycles[0].cycle_hits, vec![2]);
+        assert_eq!(cycles[1].cycle_hits, vec![3]);
+        assert_eq!(first_simultaneous_arrival(&cycles), Ok(11));
+    }
+
+    #[test]
+    fn test_prefix_hits() {
+        // The first ghost passes a goal once on the way into its loop
+        let input = indoc! {"
+            L
+
+            11A = (11Z, 11Z)
+            11Z = (11B, 11B)
+            11B = (11C, 11C)
+            11C = (11B, 11B)
+            22A = (22Z, 22Z)
+            22Z = (22Z, 22Z)
+            "};
+
+        let cycles = cycles(input);
+        assert_eq!(cycles[0].prefix_hits, vec![1]);
+        assert!(cycles[0].cycle_hits.is_empty());
+        assert_eq!(first_simultaneous_arrival(&cycles), Ok(1));
+    }
+
+    #[test]
+    fn test_no_simultaneous_arrival() {
+        // Both ghosts loop every two steps, but are on goals at different parities
+        let input = indoc! {"
+            L
+
+            11A = (11Z, 11Z)
+            11Z = (11B, 11B)
+            11B = (11Z, 11Z)
+            22A = (22B, 22B)
+            22B = (22Z, 22Z)
+            22Z = (22B, 22B)
+            "};
+
+        let cycles = cycles(input);
+        assert_eq!(
+            first_simultaneous_arrival(&cycles),
+            Err(GhostError::NoSimultaneousArrival)
+        );
+        assert_eq!(
+            first_simultaneous_arrival(&[]),
+            Err(GhostError::NoStartingPoints)
+        );
+    }
+
+    #[test]
+    fn test_problem_two() {
+        let input = include_str!("problem_text");
+        assert_eq!(cycles(input).len(), 6);
+        assert_eq!(
+            first_simultaneous_arrival(&cycles(input)),
+            Ok(14449445933179)
+        );
+    }
+}
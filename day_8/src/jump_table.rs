@@ -0,0 +1,240 @@
+use crate::network::{Network, NodeId};
+
+/// Answer a query about a walker starting from `node`, either its position after `steps`
+/// steps (`at`) or the first goal step at or after `steps` (`goal`)
+pub(crate) fn query(input: &str, query: &str, node: &str, steps: u64) {
+    let network = match Network::parse(input) {
+        Ok(network) => network,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    let Some(start) = network.id(node) else {
+        eprintln!("No node {}", node);
+        return;
+    };
+
+    let table = JumpTable::new(&network, steps);
+    match query {
+        "at" => {
+            let end = table.position_after(start, steps);
+            println!("After {} steps: {}", steps, network.name(end).0);
+        }
+        "goal" => match table.first_goal_from(start, steps) {
+            Some(step) => println!("First goal at or after {}: {}", steps, step),
+            None => println!("No goal is reached after {}", steps),
+        },
+        _ => eprintln!("Unknown query {}, expected one of: at, goal", query),
+    }
+}
+
+/// Binary lifting over the states of a walk, a state being a node together with the
+/// position in the instructions.
+///
+/// Level `k` holds the state reached after `2^k` steps from every state, and whether a
+/// goal node is passed along the way, so any number of steps can be taken as a sum of
+/// powers of two.
+pub(crate) struct JumpTable {
+    n_instructions: usize,
+    /// `jumps[k][state]` is the state `2^k` steps later
+    jumps: Vec<Vec<u32>>,
+    /// `has_goal[k][state]` is true if a goal is reached in the next `2^k` steps
+    has_goal: Vec<Vec<bool>>,
+    is_goal: Vec<bool>,
+}
+
+impl JumpTable {
+    /// Build a table able to answer queries up to `max_steps`.
+    ///
+    /// There are always enough levels to look further ahead than the number of states,
+    /// which is long enough for any walk to repeat itself.
+    pub(crate) fn new(network: &Network, max_steps: u64) -> JumpTable {
+        let n_instructions = network.instructions.len();
+        let n_states = network.n_states();
+        assert!(
+            n_states < u32::MAX as usize,
+            "Too many states for a jump table"
+        );
+
+        let horizon = max_steps.max(n_states as u64);
+        let n_levels = (u64::BITS - horizon.leading_zeros()).max(1) as usize;
+
+        let state = |node: NodeId, idx: usize| node as usize * n_instructions + idx;
+        let is_goal = (0..n_states)
+            .map(|s| network.is_goal((s / n_instructions) as NodeId))
+            .collect::<Vec<_>>();
+
+        let first = (0..n_states)
+            .map(|s| {
+                let (node, idx) = (s / n_instructions, s % n_instructions);
+                let next = network.next(node as NodeId, network.instructions[idx]);
+                state(next, (idx + 1) % n_instructions) as u32
+            })
+            .collect::<Vec<_>>();
+        let first_goal = first
+            .iter()
+            .map(|&s| is_goal[s as usize])
+            .collect::<Vec<_>>();
+
+        let mut jumps = vec![first];
+        let mut has_goal = vec![first_goal];
+        for k in 1..n_levels {
+            let (prev_jumps, prev_goal) = (&jumps[k - 1], &has_goal[k - 1]);
+            let next_jumps = (0..n_states)
+                .map(|s| prev_jumps[prev_jumps[s] as usize])
+                .collect();
+            let next_goal = (0..n_states)
+                .map(|s| prev_goal[s] || prev_goal[prev_jumps[s] as usize])
+                .collect();
+            jumps.push(next_jumps);
+            has_goal.push(next_goal);
+        }
+
+        JumpTable {
+            n_instructions,
+            jumps,
+            has_goal,
+            is_goal,
+        }
+    }
+
+    /// The largest number of steps that can be taken in a single query
+    pub(crate) fn max_steps(&self) -> u64 {
+        u64::MAX >> (u64::BITS as usize - self.jumps.len())
+    }
+
+    /// The node a walker starting at the beginning of the instructions is on after
+    /// `steps` steps
+    pub(crate) fn position_after(&self, start: NodeId, steps: u64) -> NodeId {
+        let state = self.advance(start as usize * self.n_instructions, steps);
+        (state / self.n_instructions) as NodeId
+    }
+
+    /// The first step, at or after `from`, at which a walker starting at the beginning of
+    /// the instructions is on a goal node. `None` if it never is.
+    pub(crate) fn first_goal_from(&self, start: NodeId, from: u64) -> Option<u64> {
+        let mut state = self.advance(start as usize * self.n_instructions, from);
+        if self.is_goal[state] {
+            return Some(from);
+        }
+
+        // Take the largest jumps that do not pass a goal, the goal is then one step away
+        let mut skipped = 0u64;
+        for k in (0..self.jumps.len()).rev() {
+            if !self.has_goal[k][state] {
+                state = self.jumps[k][state] as usize;
+                skipped += 1 << k;
+            }
+        }
+        if !self.has_goal[0][state] {
+            return None;
+        }
+        from.checked_add(skipped)?.checked_add(1)
+    }
+
+    fn advance(&self, mut state: usize, steps: u64) -> usize {
+        assert!(
+            steps <= self.max_steps(),
+            "The jump table only covers {} steps",
+            self.max_steps()
+        );
+
+        for (k, jumps) in self.jumps.iter().enumerate() {
+            if steps >> k & 1 == 1 {
+                state = jumps[state] as usize;
+            }
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ghosts::GhostCycle;
+    use indoc::indoc;
+
+    const SAMPLE: &str = indoc! {"
+        LR
+
+        11A = (11B, XXX)
+        11B = (XXX, 11Z)
+        11Z = (11B, XXX)
+        22A = (22B, XXX)
+        22B = (22C, 22C)
+        22C = (22Z, 22Z)
+        22Z = (22B, 22B)
+        XXX = (XXX, XXX)
+        "};
+
+    fn walk(network: &Network, start: NodeId, steps: usize) -> NodeId {
+        let tape = network.instructions.iter().cycle().take(steps);
+        tape.fold(start, |node, &instruction| network.next(node, instruction))
+    }
+
+    #[test]
+    fn test_position_matches_walk() {
+        let network = Network::parse(SAMPLE).unwrap();
+        let table = JumpTable::new(&network, 1000);
+
+        for start in 0..network.len() as NodeId {
+            for steps in 0..100 {
+                let expected = walk(&network, start, steps);
+                assert_eq!(table.position_after(start, steps as u64), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_steps() {
+        let network = Network::parse(SAMPLE).unwrap();
+        let table = JumpTable::new(&network, 10u64.pow(18));
+        let (ghost_1, ghost_2) = (network.id("11A").unwrap(), network.id("22A").unwrap());
+
+        // The first ghost is on 11Z on even steps, the second on 22Z every third step
+        let steps = 10u64.pow(18);
+        assert_eq!(
+            table.position_after(ghost_1, steps),
+            network.id("11Z").unwrap()
+        );
+        assert_eq!(
+            table.position_after(ghost_2, steps),
+            network.id("22B").unwrap()
+        );
+        assert_eq!(table.first_goal_from(ghost_2, steps), Some(steps + 2));
+    }
+
+    #[test]
+    fn test_first_goal() {
+        let network = Network::parse(SAMPLE).unwrap();
+        let table = JumpTable::new(&network, 1000);
+        let ghost_1 = network.id("11A").unwrap();
+
+        assert_eq!(table.first_goal_from(ghost_1, 0), Some(2));
+        assert_eq!(table.first_goal_from(ghost_1, 2), Some(2));
+        assert_eq!(table.first_goal_from(ghost_1, 3), Some(4));
+        assert_eq!(table.first_goal_from(network.id("XXX").unwrap(), 0), None);
+    }
+
+    #[test]
+    fn test_problem_goals() {
+        let network = Network::parse(include_str!("problem_text")).unwrap();
+        let table = JumpTable::new(&network, u64::MAX);
+        assert_eq!(table.max_steps(), u64::MAX);
+
+        let start = network.id("AAA").unwrap();
+        assert_eq!(table.first_goal_from(start, 1), Some(18023));
+
+        // Every ghost is on a goal at the answer to problem two
+        let answer = 14449445933179;
+        for start in network.starts() {
+            let cycle = GhostCycle::find(&network, start);
+            assert_eq!(
+                table.first_goal_from(start, 1),
+                cycle.cycle_hits.first().copied()
+            );
+            assert_eq!(table.first_goal_from(start, answer), Some(answer));
+        }
+    }
+}
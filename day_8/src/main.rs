@@ -1,117 +1,158 @@
+mod benchmark;
+mod dot;
+mod ghosts;
+mod jump_table;
+mod network;
+mod reachability;
+mod selector;
+
+use dot::{to_dot, DotOptions};
+use ghosts::{first_simultaneous_arrival, GhostCycle, GhostError};
+use network::Network;
 use nom::bytes::complete::tag;
 use nom::character::complete::space1;
 use nom::character::complete::{alphanumeric1, multispace1};
-use num::integer::lcm;
 use nom::multi::{many1, separated_list1};
 use nom::sequence::{delimited, pair, separated_pair};
 use nom::IResult;
 use nom::Parser;
 use nom_supreme::ParserExt;
+use reachability::{first_goal_step, ReachabilityReport};
+use selector::NodeSelector;
 use std::collections::HashMap;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [command, rest @ ..] if command == "bench" => {
+            let n_nodes = rest
+                .first()
+                .map_or(500_000, |s| s.parse().expect("Invalid number of nodes"));
+            let n_steps = rest
+                .get(1)
+                .map_or(10_000_000, |s| s.parse().expect("Invalid number of steps"));
+            benchmark::benchmark(n_nodes, n_steps);
+            return;
+        }
+        [command, query, node, steps] if command == "query" => {
+            let steps = steps.parse().expect("Invalid number of steps");
+            jump_table::query(include_str!("problem_text"), query, node, steps);
+            return;
+        }
+        [command, rest @ ..] if command == "reach" => {
+            let starts = rest
+                .first()
+                .map_or(NodeSelector::ghost_starts(), |s| NodeSelector::new(s));
+            let goals = rest
+                .get(1)
+                .map_or(NodeSelector::ghost_goals(), |s| NodeSelector::new(s));
+            report_reachability(include_str!("problem_text"), &starts, &goals);
+            return;
+        }
+        [command, rest @ ..] if command == "dot" => {
+            export_dot(include_str!("problem_text"), rest);
+            return;
+        }
+        _ => {}
+    }
+
     problem_one();
     problem_two();
 }
 
 fn problem_one() {
     let input = include_str!("problem_text");
-    let result = count_steps(input);
-    println!("Result: {:?}", result);
+    match count_steps(input) {
+        Ok(result) => println!("Result: {}", result),
+        Err(e) => println!("Error: {}", e),
+    }
 }
 
 fn problem_two() {
     let input = include_str!("problem_text");
-    let result = parallel_cycles(input);
-    println!("Result: {:?}", result);
+    match parallel_cycles(input) {
+        Ok(result) => println!("Result: {}", result),
+        Err(e) => println!("Error: {}", e),
+    }
 }
 
-fn count_steps(input: &str) -> u64 {
-    let (_, (instructions, network)) = parse_input(input).unwrap();
-    let mut start = Element::new("AAA");
+/// Print which goals each start can reach, and when the ghosts all arrive together
+fn report_reachability(input: &str, starts: &NodeSelector, goals: &NodeSelector) {
+    let network = match Network::parse_with(input, starts, goals) {
+        Ok(network) => network,
+        Err(e) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
 
-    let mut steps = 0;
-    for instruction in instructions.repeat(1000) {
-        steps += 1;
+    let report = ReachabilityReport::new(&network);
+    print!("{}", report);
+    if report.stranded().next().is_some() {
+        return;
+    }
+    match ghost_arrival(&network) {
+        Ok(result) => println!("All ghosts arrive at step {}", result),
+        Err(e) => println!("Error: {}", e),
+    }
+}
 
-        match instruction {
-            Instruction::L => {
-                let either = network.get(&start).unwrap();
-                start = either.left.clone();
-            }
-            Instruction::R => {
-                let either = network.get(&start).unwrap();
-                start = either.right.clone();
-            }
-        };
+/// Print the network as a GraphViz digraph. With a node, its walk is overlaid, and with
+/// `reachable` after it only the nodes it can reach are drawn.
+fn export_dot(input: &str, args: &[String]) {
+    let network = match Network::parse(input) {
+        Ok(network) => network,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
 
-        if start == Element::new("ZZZ") {
-            break;
+    let mut options = DotOptions::default();
+    if let Some(node) = args.first() {
+        let Some(start) = network.id(node) else {
+            eprintln!("No node {}", node);
+            return;
+        };
+        options.walk_from = Some(start);
+        if args.get(1).map(String::as_str) == Some("reachable") {
+            options.reachable_from = Some(start);
         }
     }
-
-    steps
+    print!("{}", to_dot(&network, &options));
 }
 
-/// This iterates through networks with multiple starting points in parallel.
-///
-/// However, for the full input this would take an extremely long time to run, so instead
-/// we note that the each starting point has a cycle length (some multiple of the instruction set
-/// length) and we can use that to calculate the number of steps.
-///
-/// The number of steps is the LCM of the cycle lengths.
-fn parallel_cycles(input: &str) -> u64 {
-    let (_, (instructions, network)) = parse_input(input).expect("Parse Error");
-
-    let mut starting_points: Vec<_> = network.keys().filter(|&key| key.0.ends_with('A')).collect();
-    let mut steps = 0;
-
-    let mut lasts = [0u64; 6];
-    let mut cycle = [0u64; 6];
-
-    for instruction in instructions.repeat(1000) {
-        steps += 1;
-
-        starting_points
-            .iter_mut()
-            .for_each(|start| match instruction {
-                Instruction::L => {
-                    let either = network.get(start).expect("No key found");
-                    let left = &either.left;
-                    *start = left;
-                }
-                Instruction::R => {
-                    let either = network.get(start).expect("No key found");
-                    let right = &either.right;
-                    *start = right;
-                }
-            });
-
-        if starting_points.iter().any(|start| start.0.ends_with('Z')) {
-            starting_points.iter().enumerate().for_each(|(i, start)| {
-                if start.0.ends_with('Z') {
-                    let delta = steps - lasts[i];
-                    cycle[i] = delta;
-                    lasts[i] = steps;
-                }
-            });
-        }
+/// Counts the steps from `AAA` to `ZZZ`
+fn count_steps(input: &str) -> Result<u64, GhostError> {
+    let start = NodeSelector::new("AAA");
+    let network = Network::parse_with(input, &start, &NodeSelector::new("ZZZ"))?;
+    let start = network.id("AAA").expect("Selected nodes exist");
 
-        if starting_points.iter().all(|start| start.0.ends_with('Z')) {
-            // In the trivial case, we can just return the number of steps
-            println!("{steps}: starting_points: {:?}", starting_points);
-            return steps;
-        }
+    first_goal_step(&network, start)
+        .ok_or_else(|| GhostError::NeverReachesGoal(network.name(start).clone()))
+}
 
-        if cycle.iter().all(|&x| x != 0) {
-            // In the non-trivial case, we can break once we know the cycle lengths
-            println!("Cycle array filled: {:?}", cycle);
-            break;
-        }
-    }
+/// Walks a ghost from every node ending in `A` at the same time, and returns the first
+/// step at which they all stand on a node ending in `Z`.
+fn parallel_cycles(input: &str) -> Result<u64, GhostError> {
+    let network = Network::parse(input)?;
+    ghost_arrival(&network)
+}
 
-    // The total cycle is the LCM of the individual cycles
-    cycle.into_iter().reduce(lcm).unwrap()
+/// This would take far too long to simulate, so instead each ghost's loop is found and
+/// the loops are combined, see `ghosts::first_simultaneous_arrival`.
+fn ghost_arrival(network: &Network) -> Result<u64, GhostError> {
+    let cycles = network
+        .starts()
+        .map(|start| {
+            let cycle = GhostCycle::find(network, start);
+            if cycle.prefix_hits.is_empty() && cycle.cycle_hits.is_empty() {
+                return Err(GhostError::NeverReachesGoal(network.name(start).clone()));
+            }
+            Ok(cycle)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    first_simultaneous_arrival(&cycles)
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
@@ -135,12 +176,23 @@ struct Either {
     right: Element,
 }
 
+impl Either {
+    fn follow(&self, instruction: Instruction) -> &Element {
+        match instruction {
+            Instruction::L => &self.left,
+            Instruction::R => &self.right,
+        }
+    }
+}
+
 fn triplet_parser(input: &str) -> IResult<&str, Element> {
     alphanumeric1.map(Element::new).parse(input)
 }
 
-fn parse_input(input: &str) -> IResult<&str, (Vec<Instruction>, HashMap<Element, Either>)> {
-    let network = parse_network;
+type Lines = Vec<(Element, Either)>;
+
+fn parse_input(input: &str) -> IResult<&str, (Vec<Instruction>, Lines)> {
+    let network = parse_lines;
     let instructions = parse_instructions;
 
     let mut parser = separated_pair(instructions, multispace1, network);
@@ -148,21 +200,20 @@ fn parse_input(input: &str) -> IResult<&str, (Vec<Instruction>, HashMap<Element,
 }
 
 fn parse_network(input: &str) -> IResult<&str, HashMap<Element, Either>> {
+    parse_lines
+        .map(|lines| lines.into_iter().collect())
+        .parse(input)
+}
+
+/// The network in the order it is written
+fn parse_lines(input: &str) -> IResult<&str, Lines> {
     let pair_parse = separated_pair(triplet_parser, pair(tag(","), space1), triplet_parser);
     let either_parser =
         delimited(tag("("), pair_parse, tag(")")).map(|(left, right)| Either { left, right });
 
     let line_parse = separated_pair(triplet_parser, tag(" = "), either_parser);
-    let parser = separated_list1(multispace1, line_parse);
-
-    parser
-        .map(|elements| {
-            elements
-                .into_iter()
-                .map(|(key, value)| (key, value))
-                .collect()
-        })
-        .parse(input)
+    let mut parser = separated_list1(multispace1, line_parse);
+    parser.parse(input)
 }
 
 fn parse_instructions(input: &str) -> IResult<&str, Vec<Instruction>> {
@@ -174,6 +225,7 @@ fn parse_instructions(input: &str) -> IResult<&str, Vec<Instruction>> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::network::NetworkError;
     use indoc::indoc;
     #[test]
     fn test_triplet_parser() {
@@ -246,7 +298,7 @@ mod test {
         let expected_count = 2;
         let actual_count = count_steps(sample_input);
 
-        assert_eq!(actual_count, expected_count);
+        assert_eq!(actual_count, Ok(expected_count));
     }
 
     #[test]
@@ -263,7 +315,26 @@ mod test {
         let expected_count = 6;
         let actual_count = count_steps(sample_input);
 
-        assert_eq!(actual_count, expected_count);
+        assert_eq!(actual_count, Ok(expected_count));
+    }
+
+    #[test]
+    fn test_unreachable_goal() {
+        let sample_input = indoc! {"
+            L
+
+            AAA = (BBB, BBB)
+            BBB = (AAA, AAA)
+            ZZZ = (ZZZ, ZZZ)
+            "
+        };
+
+        let expected = GhostError::NeverReachesGoal(Element::new("AAA"));
+        assert_eq!(count_steps(sample_input), Err(expected));
+
+        let missing_goal = "L\n\nAAA = (AAA, AAA)";
+        let expected = GhostError::Network(NetworkError::UnknownNode(Element::new("ZZZ")));
+        assert_eq!(count_steps(missing_goal), Err(expected));
     }
 
     #[test]
@@ -284,6 +355,21 @@ mod test {
         let expected_count = 6;
         let actual_count = parallel_cycles(sample_input);
 
-        assert_eq!(actual_count, expected_count);
+        assert_eq!(actual_count, Ok(expected_count));
+    }
+
+    #[test]
+    fn test_par_never_reaches_goal() {
+        let sample_input = indoc! {"
+            L
+
+            11A = (11Z, 11Z)
+            11Z = (11Z, 11Z)
+            22A = (22B, 22B)
+            22B = (22A, 22A)
+            "};
+
+        let expected = GhostError::NeverReachesGoal(Element::new("22A"));
+        assert_eq!(parallel_cycles(sample_input), Err(expected));
     }
 }
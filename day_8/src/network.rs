@@ -0,0 +1,233 @@
+use crate::selector::NodeSelector;
+use crate::{parse_input, Element, Instruction, Lines};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// Dense index of a node in a `Network`
+pub(crate) type NodeId = u32;
+
+/// A parsed network where every node name is interned to a `NodeId`, so walking it only
+/// indexes into flat arrays.
+#[derive(Debug)]
+pub(crate) struct Network {
+    pub(crate) instructions: Vec<Instruction>,
+    names: Vec<Element>,
+    ids: HashMap<Element, NodeId>,
+    /// The left and right neighbour of each node, indexed by `Instruction`
+    edges: Vec<[NodeId; 2]>,
+    is_start: Vec<bool>,
+    is_goal: Vec<bool>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum NetworkError {
+    Parse(String),
+    DuplicateNode(Element),
+    /// A node points to a node that is not defined
+    MissingTarget {
+        node: Element,
+        target: Element,
+    },
+    /// A selector names a node that is not defined
+    UnknownNode(Element),
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::Parse(message) => write!(f, "Parse error: {}", message),
+            NetworkError::DuplicateNode(node) => write!(f, "Node {} is defined twice", node.0),
+            NetworkError::MissingTarget { node, target } => {
+                write!(
+                    f,
+                    "Node {} points to {}, which is not defined",
+                    node.0, target.0
+                )
+            }
+            NetworkError::UnknownNode(node) => write!(f, "No node {}", node.0),
+        }
+    }
+}
+
+impl Network {
+    /// Parse a network with the ghost starts and goals, see `NodeSelector`
+    pub(crate) fn parse(input: &str) -> Result<Network, NetworkError> {
+        Network::parse_with(
+            input,
+            &NodeSelector::ghost_starts(),
+            &NodeSelector::ghost_goals(),
+        )
+    }
+
+    pub(crate) fn parse_with(
+        input: &str,
+        starts: &NodeSelector,
+        goals: &NodeSelector,
+    ) -> Result<Network, NetworkError> {
+        let (rest, (instructions, lines)) =
+            parse_input(input).map_err(|e| NetworkError::Parse(e.to_string()))?;
+        if let Some(line) = rest.lines().map(str::trim).find(|line| !line.is_empty()) {
+            return Err(NetworkError::Parse(format!("Unexpected line: {}", line)));
+        }
+        Network::from_lines(instructions, lines, starts, goals)
+    }
+
+    /// Nodes are numbered in the order they are defined, so ids are stable for a given
+    /// input.
+    pub(crate) fn from_lines(
+        instructions: Vec<Instruction>,
+        lines: Lines,
+        starts: &NodeSelector,
+        goals: &NodeSelector,
+    ) -> Result<Network, NetworkError> {
+        let (names, eithers): (Vec<_>, Vec<_>) = lines.into_iter().unzip();
+        let mut ids = HashMap::with_capacity(names.len());
+        for (id, name) in names.iter().enumerate() {
+            if ids.insert(name.clone(), id as NodeId).is_some() {
+                return Err(NetworkError::DuplicateNode(name.clone()));
+            }
+        }
+
+        let lookup = |node: &Element, target: &Element| {
+            ids.get(target)
+                .copied()
+                .ok_or_else(|| NetworkError::MissingTarget {
+                    node: node.clone(),
+                    target: target.clone(),
+                })
+        };
+        let edges = names
+            .iter()
+            .zip(eithers.iter())
+            .map(|(node, either)| Ok([lookup(node, &either.left)?, lookup(node, &either.right)?]))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for selector in [starts, goals] {
+            if let Some(node) = selector
+                .explicit_nodes()
+                .find(|&node| !ids.contains_key(&Element::new(node)))
+            {
+                return Err(NetworkError::UnknownNode(Element::new(node)));
+            }
+        }
+
+        Ok(Network {
+            instructions,
+            is_start: names.iter().map(|name| starts.matches(&name.0)).collect(),
+            is_goal: names.iter().map(|name| goals.matches(&name.0)).collect(),
+            names,
+            ids,
+            edges,
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub(crate) fn id(&self, name: &str) -> Option<NodeId> {
+        self.ids.get(&Element::new(name)).copied()
+    }
+
+    pub(crate) fn name(&self, node: NodeId) -> &Element {
+        &self.names[node as usize]
+    }
+
+    pub(crate) fn next(&self, node: NodeId, instruction: Instruction) -> NodeId {
+        self.edges[node as usize][instruction as usize]
+    }
+
+    /// The starting nodes, in id order
+    pub(crate) fn starts(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.len() as NodeId).filter(|&node| self.is_start(node))
+    }
+
+    pub(crate) fn is_start(&self, node: NodeId) -> bool {
+        self.is_start[node as usize]
+    }
+
+    pub(crate) fn is_goal(&self, node: NodeId) -> bool {
+        self.is_goal[node as usize]
+    }
+
+    /// The number of distinct (node, instruction) states a walk can be in
+    pub(crate) fn n_states(&self) -> usize {
+        self.len() * self.instructions.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    const SAMPLE: &str = indoc! {"
+        LR
+
+        11A = (11B, XXX)
+        11B = (XXX, 11Z)
+        11Z = (11B, XXX)
+        22A = (22B, XXX)
+        22B = (22C, 22C)
+        22C = (22Z, 22Z)
+        22Z = (22B, 22B)
+        XXX = (XXX, XXX)
+        "};
+
+    #[test]
+    fn test_interning() {
+        let network = Network::parse(SAMPLE).unwrap();
+        assert_eq!(network.len(), 8);
+        assert_eq!(network.instructions, vec![Instruction::L, Instruction::R]);
+
+        // Ids follow the order of definition
+        assert_eq!(network.id("11A"), Some(0));
+        assert_eq!(network.id("XXX"), Some(7));
+        assert_eq!(network.id("33A"), None);
+        assert_eq!(network.name(6), &Element::new("22Z"));
+
+        assert_eq!(network.next(0, Instruction::L), 1);
+        assert_eq!(network.next(0, Instruction::R), 7);
+        assert_eq!(network.next(1, Instruction::R), 2);
+    }
+
+    #[test]
+    fn test_validation() {
+        let missing = "L\n\nAAA = (BBB, AAA)";
+        let expected = NetworkError::MissingTarget {
+            node: Element::new("AAA"),
+            target: Element::new("BBB"),
+        };
+        assert_eq!(Network::parse(missing).unwrap_err(), expected);
+
+        let duplicate = "L\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)";
+        let expected = NetworkError::DuplicateNode(Element::new("AAA"));
+        assert_eq!(Network::parse(duplicate).unwrap_err(), expected);
+
+        let trailing = "L\n\nAAA = (AAA, AAA)\nBBB = CCC";
+        let expected = NetworkError::Parse("Unexpected line: BBB = CCC".to_string());
+        assert_eq!(Network::parse(trailing).unwrap_err(), expected);
+
+        let starts = NodeSelector::new("11A,44A");
+        let result = Network::parse_with(SAMPLE, &starts, &NodeSelector::ghost_goals());
+        let expected = NetworkError::UnknownNode(Element::new("44A"));
+        assert_eq!(result.unwrap_err(), expected);
+    }
+
+    #[test]
+    fn test_masks() {
+        let network = Network::parse(SAMPLE).unwrap();
+        assert_eq!(network.starts().collect::<Vec<_>>(), vec![0, 3]);
+
+        let ends = (0..network.len() as NodeId)
+            .filter(|&node| network.is_goal(node))
+            .collect::<Vec<_>>();
+        assert_eq!(ends, vec![2, 6]);
+
+        let starts = NodeSelector::new("22?");
+        let goals = NodeSelector::new("XXX, 11B");
+        let network = Network::parse_with(SAMPLE, &starts, &goals).unwrap();
+        assert_eq!(network.starts().collect::<Vec<_>>(), vec![3, 4, 5, 6]);
+        assert!(network.is_goal(1) && network.is_goal(7) && !network.is_goal(2));
+    }
+}
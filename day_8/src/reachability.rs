@@ -0,0 +1,135 @@
+use crate::network::{Network, NodeId};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// The first step at which a walker from `start` stands on a goal, or `None` if it never
+/// does.
+///
+/// Once a walk has taken as many steps as there are states it must have repeated one, so
+/// there is no need to walk any further than that.
+pub(crate) fn first_goal_step(network: &Network, start: NodeId) -> Option<u64> {
+    let tape = network.instructions.iter().cycle();
+    let mut node = start;
+    for (step, &instruction) in tape.take(network.n_states()).enumerate() {
+        node = network.next(node, instruction);
+        if network.is_goal(node) {
+            return Some(step as u64 + 1);
+        }
+    }
+    None
+}
+
+/// The goals that a walker from `start` ever stands on, with the first step it does so
+pub(crate) fn reachable_goals(network: &Network, start: NodeId) -> BTreeMap<NodeId, u64> {
+    let n_instructions = network.instructions.len();
+    let mut visited = vec![false; network.n_states()];
+    let mut goals = BTreeMap::new();
+
+    let (mut node, mut idx) = (start, 0);
+    for step in 1.. {
+        node = network.next(node, network.instructions[idx]);
+        idx = (idx + 1) % n_instructions;
+
+        let state = node as usize * n_instructions + idx;
+        if visited[state] {
+            break;
+        }
+        visited[state] = true;
+
+        if network.is_goal(node) {
+            goals.entry(node).or_insert(step);
+        }
+    }
+    goals
+}
+
+/// Which goals can be reached from each start
+pub(crate) struct ReachabilityReport<'a> {
+    network: &'a Network,
+    rows: Vec<(NodeId, BTreeMap<NodeId, u64>)>,
+}
+
+impl ReachabilityReport<'_> {
+    pub(crate) fn new(network: &Network) -> ReachabilityReport<'_> {
+        let rows = network
+            .starts()
+            .map(|start| (start, reachable_goals(network, start)))
+            .collect();
+        ReachabilityReport { network, rows }
+    }
+
+    /// The starts that can never reach any goal
+    pub(crate) fn stranded(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.rows
+            .iter()
+            .filter(|(_, goals)| goals.is_empty())
+            .map(|(start, _)| *start)
+    }
+}
+
+impl Display for ReachabilityReport<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (start, goals) in self.rows.iter() {
+            write!(f, "{}:", self.network.name(*start).0)?;
+            if goals.is_empty() {
+                write!(f, " no goals")?;
+            }
+            for (goal, step) in goals.iter() {
+                write!(f, " {} (step {})", self.network.name(*goal).0, step)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::selector::NodeSelector;
+    use indoc::indoc;
+
+    const SAMPLE: &str = indoc! {"
+        LR
+
+        11A = (11B, XXX)
+        11B = (XXX, 11Z)
+        11Z = (11B, XXX)
+        22A = (22B, XXX)
+        22B = (22C, 22C)
+        22C = (22Z, 22Z)
+        22Z = (22B, 22B)
+        33A = (XXX, XXX)
+        XXX = (XXX, XXX)
+        "};
+
+    #[test]
+    fn test_first_goal_step() {
+        let network = Network::parse(SAMPLE).unwrap();
+        let steps = network
+            .starts()
+            .map(|start| first_goal_step(&network, start))
+            .collect::<Vec<_>>();
+        assert_eq!(steps, vec![Some(2), Some(3), None]);
+    }
+
+    #[test]
+    fn test_reachable_goals() {
+        let goals = NodeSelector::new("*Z,22C");
+        let network = Network::parse_with(SAMPLE, &NodeSelector::ghost_starts(), &goals).unwrap();
+        let id = |name| network.id(name).unwrap();
+
+        let expected = BTreeMap::from([(id("22C"), 2), (id("22Z"), 3)]);
+        assert_eq!(reachable_goals(&network, id("22A")), expected);
+        assert_eq!(reachable_goals(&network, id("XXX")), BTreeMap::new());
+
+        let report = ReachabilityReport::new(&network);
+        assert_eq!(report.stranded().collect::<Vec<_>>(), vec![id("33A")]);
+        let expected_report = indoc! {"
+            11A: 11Z (step 2)
+            22A: 22C (step 2) 22Z (step 3)
+            33A: no goals
+            "};
+        assert_eq!(report.to_string(), expected_report);
+    }
+}
@@ -0,0 +1,89 @@
+/// Selects nodes by name from a comma separated list of patterns, where `*` matches any
+/// run of characters and `?` matches a single character. A pattern without wildcards
+/// names a single node, which must exist in the network.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) struct NodeSelector {
+    patterns: Vec<String>,
+}
+
+impl NodeSelector {
+    pub(crate) fn new(patterns: &str) -> NodeSelector {
+        let patterns = patterns
+            .split(',')
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(str::to_string)
+            .collect();
+        NodeSelector { patterns }
+    }
+
+    /// The starting nodes for ghosts, every node ending in `A`
+    pub(crate) fn ghost_starts() -> NodeSelector {
+        NodeSelector::new("*A")
+    }
+
+    /// The goal nodes for ghosts, every node ending in `Z`
+    pub(crate) fn ghost_goals() -> NodeSelector {
+        NodeSelector::new("*Z")
+    }
+
+    pub(crate) fn matches(&self, name: &str) -> bool {
+        let name = name.chars().collect::<Vec<_>>();
+        self.patterns.iter().any(|pattern| {
+            let pattern = pattern.chars().collect::<Vec<_>>();
+            glob_match(&pattern, &name)
+        })
+    }
+
+    /// The patterns that name a node directly
+    pub(crate) fn explicit_nodes(&self) -> impl Iterator<Item = &str> {
+        self.patterns
+            .iter()
+            .map(String::as_str)
+            .filter(|pattern| !pattern.contains(['*', '?']))
+    }
+}
+
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some('*'), _) => {
+            // Either the star matches nothing, or it swallows one more character
+            glob_match(&pattern[1..], name) || (!name.is_empty() && glob_match(pattern, &name[1..]))
+        }
+        (Some('?'), Some(_)) => glob_match(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => glob_match(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_patterns() {
+        let starts = NodeSelector::ghost_starts();
+        assert!(starts.matches("11A"));
+        assert!(starts.matches("A"));
+        assert!(!starts.matches("AAZ"));
+
+        let selector = NodeSelector::new("AAA, 1?B,*X*");
+        assert!(selector.matches("AAA"));
+        assert!(selector.matches("12B"));
+        assert!(!selector.matches("123B"));
+        assert!(selector.matches("X"));
+        assert!(selector.matches("1X2"));
+        assert!(!selector.matches("AAB"));
+    }
+
+    #[test]
+    fn test_explicit_nodes() {
+        let selector = NodeSelector::new("AAA,*Z,BBB");
+        assert_eq!(
+            selector.explicit_nodes().collect::<Vec<_>>(),
+            vec!["AAA", "BBB"]
+        );
+        assert_eq!(NodeSelector::new("").explicit_nodes().count(), 0);
+    }
+}
@@ -0,0 +1,60 @@
+/// Small seeded generator shared by the days that generate inputs or simulate games, so
+/// that their output is repeatable without extra dependencies
+pub struct XorShift(u64);
+
+impl XorShift {
+    pub fn new(seed: u64) -> Self {
+        XorShift(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a value in `0..n`, the bias is negligible for small `n`
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "There are no values below zero");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_repeatable() {
+        let mut a = XorShift::new(42);
+        let mut b = XorShift::new(42);
+        assert!((0..100).all(|_| a.next_u64() == b.next_u64()));
+
+        // A zero seed would only ever give zero
+        assert_ne!(XorShift::new(0).next_u64(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "There are no values below zero")]
+    fn test_below_zero() {
+        XorShift::new(1).below(0);
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let mut items = (0..20).collect::<Vec<_>>();
+        XorShift::new(7).shuffle(&mut items);
+        assert_ne!(items, (0..20).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+}